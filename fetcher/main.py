@@ -1,6 +1,7 @@
 from selenium import webdriver
 import os
 import csv
+import json
 import argparse
 from path import get_path
 
@@ -8,7 +9,23 @@ from path import get_path
 """ Fetches all provided URLs and saves their HTML content to files."""
 
 
+def load_manifest(path: str) -> dict[str, str]:
+    """Read URL to file path mapping written by `benchmark manifest`."""
+
+    manifest_path = os.path.join(path, "manifest.json")
+
+    if not os.path.exists(manifest_path):
+        return {}
+
+    with open(manifest_path, "r") as file:
+        manifest = json.load(file)
+
+    return {entry["url"]: entry["path"] for entry in manifest["entries"]}
+
+
 def main(path: str):
+    manifest = load_manifest(path)
+
     with open(path + "dataset.csv", "r") as file:
         reader = csv.DictReader(file)
         entries = list(reader)
@@ -17,7 +34,7 @@ def main(path: str):
 
         for entry in entries:
             url = entry["url"]
-            filepath = path + "/" + get_path(url)
+            filepath = path + "/" + manifest.get(url, get_path(url))
             os.makedirs(os.path.dirname(filepath), exist_ok=True)
 
             if not os.path.exists(filepath):
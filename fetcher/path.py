@@ -1,3 +1,7 @@
+INDEX = "index.html"
+EXTENSION = ".html"
+
+
 def get_domain(url: str) -> str:
     """Extract domain from URL."""
     if url.startswith(("http://", "https://")):
@@ -10,21 +14,20 @@ def get_domain(url: str) -> str:
 def get_path(url: str) -> str:
     """Convert URL to file path by removing protocol and domain.
 
+    Must match `url_to_path` in ncd/brotli-benchmark/src/lib/url_path.rs;
+    both are checked against dataset/url_paths.csv.
+
     Example: "https://en.wikipedia.org/wiki/Abbasiya" -> "wiki/Abbasiya.html"
     """
 
-    if url.startswith(("http://", "https://")):
+    if "://" in url:
         url = url.split("://", 1)[1]
 
-    parts = url.split("/")
-
-    if len(parts) > 1:
-        path_parts = parts[1:]
-
-        if path_parts and path_parts[-1] == "":
-            path_parts[-1] = "index.html"
-            return "/".join(path_parts)
+    url = url.split("#", 1)[0]
+    starts = [index for index in (url.find("/"), url.find("?")) if index != -1]
+    path = url[min(starts) :].lstrip("/") if starts else ""
 
-        return "/".join(path_parts) + ".html"
+    if path == "" or path.endswith("/"):
+        return path + INDEX
 
-    return ""
+    return path + EXTENSION
@@ -1,3 +1,4 @@
+import csv
 import re
 import unittest
 import sys
@@ -8,6 +9,10 @@ sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
 
 from fetcher.path import get_path
 
+FIXTURES = os.path.join(
+    os.path.dirname(os.path.abspath(__file__)), "..", "..", "dataset", "url_paths.csv"
+)
+
 
 def is_valid_filename(filename: str) -> bool:
     """Check if filename is valid for the current operating system"""
@@ -56,6 +61,16 @@ class TestPagename(unittest.TestCase):
         expected = "wiki/index.html"
         self.assertEqual(get_path(url), expected)
 
+    def test_shared_fixtures(self):
+        """Test URLs shared with the Rust Dataset conformance test."""
+        with open(FIXTURES, "r") as file:
+            reader = csv.reader(file, skipinitialspace=True)
+            next(reader)
+
+            for url, expected in reader:
+                with self.subTest(url=url):
+                    self.assertEqual(get_path(url), expected)
+
 
 if __name__ == "__main__":
     unittest.main()
@@ -0,0 +1,137 @@
+use benchmark::benchmarks::{get_dataset_path, DATASETS};
+use benchmark::configuration::Stripper;
+use benchmark::dataset::Dataset;
+use benchmark::synthetic::{Generator, Template};
+use core::compress::{brotli::CompressBrotli, Compressor};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::sync::OnceLock;
+
+const SEED: u64 = 42;
+
+struct Page {
+    dataset: String,
+    name: String,
+    html: String,
+}
+
+// One page per page type of every dataset, or synthetic pages when no crawl is checked out.
+// The name of the page set goes into every benchmark group, so results are never compared
+// across sets by accident. The pages are loaded once and shared by every benchmark.
+fn pages() -> &'static (&'static str, Vec<Page>) {
+    static PAGES: OnceLock<(&'static str, Vec<Page>)> = OnceLock::new();
+    PAGES.get_or_init(load_pages)
+}
+
+fn load_pages() -> (&'static str, Vec<Page>) {
+    let mut pages = Vec::new();
+
+    for dataset_name in DATASETS {
+        let dataset = match Dataset::new(get_dataset_path(dataset_name)) {
+            Ok(dataset) => dataset,
+            Err(error) => {
+                eprintln!("Skipping {dataset_name}: {error}");
+                continue;
+            }
+        };
+        let mut by_type = BTreeMap::new();
+
+        for entry in dataset.entries() {
+            if !by_type.contains_key(&entry.page_type) {
+                if let Ok(html) = entry.get_content() {
+                    by_type.insert(entry.page_type.clone(), html);
+                }
+            }
+        }
+
+        if by_type.is_empty() {
+            eprintln!("Skipping {dataset_name}: no page could be read");
+        }
+
+        pages.extend(by_type.into_iter().map(|(page_type, html)| Page {
+            dataset: dataset_name.to_string(),
+            name: format!("{dataset_name}/{page_type}"),
+            html,
+        }));
+    }
+
+    if !pages.is_empty() {
+        return ("datasets", pages);
+    }
+
+    eprintln!("No crawled pages found, benchmarking synthetic pages instead");
+    let mut generator = Generator::recommended(SEED);
+    let pages = Template::all()
+        .into_iter()
+        .map(|template| Page {
+            dataset: "synthetic".to_string(),
+            name: format!("synthetic/{}", template.name()),
+            html: generator.page(template, 0).html,
+        })
+        .collect();
+
+    ("synthetic", pages)
+}
+
+fn strip_html(c: &mut Criterion) {
+    let (source, pages) = pages();
+    let mut group = c.benchmark_group(format!("strip_html/{source}"));
+
+    for stripper in Stripper::all() {
+        let built = stripper.build();
+        for page in pages {
+            group.throughput(Throughput::Bytes(page.html.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(stripper.name(), &page.name),
+                &page.html,
+                |b, html| b.iter(|| built.strip_html(black_box(html))),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+fn get_compressed_size(c: &mut Criterion) {
+    let compressor = CompressBrotli::recommended();
+    let (source, pages) = pages();
+    let mut group = c.benchmark_group(format!("get_compressed_size/{source}"));
+
+    for page in pages {
+        let skeleton = core::skeleton(&page.html);
+        group.throughput(Throughput::Bytes(skeleton.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("brotli", &page.name),
+            skeleton.as_bytes(),
+            |b, skeleton| b.iter(|| compressor.get_compressed_size(black_box(skeleton))),
+        );
+    }
+
+    group.finish();
+}
+
+fn calculate(c: &mut Criterion) {
+    let (source, pages) = pages();
+    let mut group = c.benchmark_group(format!("calculate/{source}"));
+
+    for pair in pages.windows(2) {
+        if pair[0].dataset != pair[1].dataset {
+            continue;
+        }
+
+        group.throughput(Throughput::Bytes(
+            (pair[0].html.len() + pair[1].html.len()) as u64,
+        ));
+        group.bench_with_input(
+            BenchmarkId::new(&pair[0].name, &pair[1].name),
+            &(&pair[0].html, &pair[1].html),
+            |b, (page_a, page_b)| b.iter(|| core::calculate(black_box(page_a), black_box(page_b))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, strip_html, get_compressed_size, calculate);
+criterion_main!(benches);
@@ -0,0 +1,473 @@
+use flate2::read::MultiGzDecoder;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::{fmt, fs};
+
+use crate::url_path::url_to_path;
+
+const DATASET_CSV: &str = "dataset.csv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    TarZst,
+    Zip,
+    Warc,
+    WarcGz,
+}
+
+impl Format {
+    pub fn detect<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_string_lossy().to_lowercase();
+
+        if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Format::TarZst)
+        } else if name.ends_with(".zip") {
+            Some(Format::Zip)
+        } else if name.ends_with(".warc") {
+            Some(Format::Warc)
+        } else if name.ends_with(".warc.gz") {
+            Some(Format::WarcGz)
+        } else {
+            None
+        }
+    }
+}
+
+// Zip members are indexed when the archive is opened and decompressed on every read. Tar.zst
+// and WARC streams have no index to seek to, so their members are decompressed once and kept
+// in memory for the lifetime of the archive.
+pub struct Archive {
+    path: PathBuf,
+    members: HashMap<String, Member>,
+    zip: Option<Mutex<zip::ZipArchive<BufReader<fs::File>>>>,
+}
+
+enum Member {
+    Loaded(Vec<u8>),
+    Zip(usize),
+}
+
+impl fmt::Debug for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Archive")
+            .field("path", &self.path)
+            .field("members", &self.members.len())
+            .finish()
+    }
+}
+
+impl Archive {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let format = Format::detect(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Unknown archive format: {}", path.display()),
+            )
+        })?;
+        let file = BufReader::new(fs::File::open(path)?);
+        let mut zip = None;
+
+        let mut members = match format {
+            Format::TarZst => loaded(read_tar(zstd::Decoder::new(file)?)?),
+            Format::Zip => {
+                let archive = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+                let members = index_zip(&archive);
+                zip = Some(Mutex::new(archive));
+                members
+            }
+            Format::Warc => loaded(read_warc(file)?),
+            Format::WarcGz => loaded(read_warc(BufReader::new(MultiGzDecoder::new(file)))?),
+        };
+
+        if !members.contains_key(DATASET_CSV) {
+            members = strip_root(members);
+        }
+
+        if !members.contains_key(DATASET_CSV) {
+            if let Some(sibling) = path.parent().map(|parent| parent.join(DATASET_CSV)) {
+                if sibling.is_file() {
+                    members.insert(DATASET_CSV.to_string(), Member::Loaded(fs::read(sibling)?));
+                }
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            members,
+            zip,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn member_path(&self, member: &str) -> PathBuf {
+        self.path.join(member)
+    }
+
+    pub fn contains(&self, member: &str) -> bool {
+        self.members.contains_key(member)
+    }
+
+    pub fn member(&self, filepath: &Path) -> String {
+        filepath
+            .strip_prefix(&self.path)
+            .map(|member| member.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default()
+    }
+
+    pub fn read(&self, filepath: &Path) -> io::Result<Cow<'_, [u8]>> {
+        let member = self.member(filepath);
+
+        self.read_member(&member)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in {}", member, self.path.display()),
+            )
+        })
+    }
+
+    pub fn read_member(&self, member: &str) -> io::Result<Option<Cow<'_, [u8]>>> {
+        match self.members.get(member) {
+            Some(Member::Loaded(content)) => Ok(Some(Cow::Borrowed(content))),
+            Some(&Member::Zip(index)) => {
+                let mut archive = self
+                    .zip
+                    .as_ref()
+                    .expect("Zip member without a zip archive")
+                    .lock()
+                    .unwrap();
+                let mut file = archive.by_index(index).map_err(io::Error::other)?;
+                let mut content = Vec::new();
+                file.read_to_end(&mut content)?;
+                Ok(Some(Cow::Owned(content)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn loaded(members: HashMap<String, Vec<u8>>) -> HashMap<String, Member> {
+    members
+        .into_iter()
+        .map(|(name, content)| (name, Member::Loaded(content)))
+        .collect()
+}
+
+fn read_tar<R: Read>(reader: R) -> io::Result<HashMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = HashMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if entry.header().entry_type().is_file() {
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            members.insert(normalize(&name), content);
+        }
+    }
+
+    Ok(members)
+}
+
+fn index_zip<R: Read + io::Seek>(archive: &zip::ZipArchive<R>) -> HashMap<String, Member> {
+    archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .filter_map(|name| {
+            let index = archive.index_for_name(name)?;
+            Some((normalize(name), Member::Zip(index)))
+        })
+        .collect()
+}
+
+fn read_warc<R: BufRead>(mut reader: R) -> io::Result<HashMap<String, Vec<u8>>> {
+    let mut members = HashMap::new();
+
+    loop {
+        let mut version = String::new();
+
+        if reader.read_line(&mut version)? == 0 {
+            break;
+        }
+
+        if version.trim().is_empty() {
+            continue;
+        }
+
+        if !version.starts_with("WARC/") {
+            return Err(invalid_data(format!(
+                "Expected WARC record, found {version:?}"
+            )));
+        }
+
+        let headers = read_headers(&mut reader)?;
+        let length = headers
+            .get("content-length")
+            .and_then(|length| length.parse::<usize>().ok())
+            .ok_or_else(|| invalid_data("WARC record without Content-Length".to_string()))?;
+        let mut block = Vec::new();
+        reader
+            .by_ref()
+            .take(length as u64)
+            .read_to_end(&mut block)?;
+
+        if block.len() < length {
+            return Err(invalid_data(format!(
+                "WARC record truncated after {} of {length} bytes",
+                block.len()
+            )));
+        }
+
+        let uri = headers
+            .get("warc-target-uri")
+            .map(|uri| uri.trim_matches(['<', '>']).to_string());
+
+        match (headers.get("warc-type").map(String::as_str), uri) {
+            (Some("response"), Some(uri)) => {
+                members.insert(url_to_path(&uri), http_body(&block)?);
+            }
+            (Some("resource"), Some(uri)) => {
+                let name = uri.strip_prefix("file:").unwrap_or(&uri);
+                members.insert(normalize(name), block);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(members)
+}
+
+fn read_headers<R: BufRead>(reader: &mut R) -> io::Result<HashMap<String, String>> {
+    let mut headers = HashMap::new();
+
+    loop {
+        let mut line = String::new();
+
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            return Ok(headers);
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+}
+
+fn http_body(block: &[u8]) -> io::Result<Vec<u8>> {
+    let mut reader = block;
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    let headers = read_headers(&mut reader)?;
+    let mut body = reader.to_vec();
+
+    if headers
+        .get("transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+    {
+        body = dechunk(&body)?;
+    }
+
+    if headers
+        .get("content-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("gzip"))
+    {
+        let mut decoded = Vec::new();
+        MultiGzDecoder::new(body.as_slice()).read_to_end(&mut decoded)?;
+        body = decoded;
+    }
+
+    Ok(body)
+}
+
+fn dechunk(mut reader: &[u8]) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+
+    loop {
+        let mut size = String::new();
+        reader.read_line(&mut size)?;
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| invalid_data(format!("Invalid chunk size {size:?}")))?;
+
+        if size == 0 {
+            return Ok(body);
+        }
+
+        if size > reader.len() {
+            return Err(invalid_data("Truncated chunk".to_string()));
+        }
+
+        body.extend_from_slice(&reader[..size]);
+        reader = &reader[size..];
+        let mut line_end = String::new();
+        reader.read_line(&mut line_end)?;
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.replace('\\', "/")
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+fn strip_root<T>(members: HashMap<String, T>) -> HashMap<String, T> {
+    let root = members
+        .keys()
+        .filter_map(|name| name.strip_suffix(DATASET_CSV))
+        .filter(|prefix| prefix.ends_with('/'))
+        .min_by_key(|prefix| prefix.len())
+        .map(str::to_string);
+
+    match root {
+        Some(root) => members
+            .into_iter()
+            .filter_map(|(name, content)| {
+                name.strip_prefix(&root)
+                    .map(|stripped| (stripped.to_string(), content))
+            })
+            .collect(),
+        None => members,
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Dataset;
+    use std::io::Write;
+
+    const CSV: &str = "url, type\n\
+                       \"https://en.wikipedia.org/wiki/Jurassic\", \"article\"\n\
+                       \"https://www.imdb.com/title/tt0111161/?ref_=chttp_t_1\", \"movie\"\n";
+    const JURASSIC: &str = "<html><body><p>Jurassic</p></body></html>";
+    const MOVIE: &str = "<html><body><h1>Movie</h1></body></html>";
+
+    fn directory(root: &Path, name: &str) -> PathBuf {
+        let directory = root.join(name);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn assert_entries(archive: &Path) {
+        let dataset = Dataset::new(archive).unwrap();
+        let contents = dataset
+            .entries()
+            .iter()
+            .map(|entry| entry.get_content().unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(contents, [JURASSIC, MOVIE]);
+        assert!(dataset.missing().is_empty());
+    }
+
+    #[test]
+    fn test_tar_zst() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = directory(temp.path(), "tar").join("crawl.tar.zst");
+        let encoder = zstd::Encoder::new(fs::File::create(&path).unwrap(), 3).unwrap();
+        let mut builder = tar::Builder::new(encoder.auto_finish());
+
+        for (name, content) in [
+            ("crawl/dataset.csv", CSV),
+            ("crawl/wiki/Jurassic.html", JURASSIC),
+            ("crawl/title/tt0111161/?ref_=chttp_t_1.html", MOVIE),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+
+        builder.into_inner().unwrap();
+        assert_entries(&path);
+    }
+
+    #[test]
+    fn test_zip() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = directory(temp.path(), "zip").join("crawl.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+
+        for (name, content) in [
+            ("dataset.csv", CSV),
+            ("wiki/Jurassic.html", JURASSIC),
+            ("title/tt0111161/?ref_=chttp_t_1.html", MOVIE),
+        ] {
+            writer
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+
+        writer.finish().unwrap();
+        assert_entries(&path);
+    }
+
+    #[test]
+    fn test_warc() {
+        let temp = tempfile::tempdir().unwrap();
+        let directory = directory(temp.path(), "warc");
+        let path = directory.join("crawl.warc");
+        fs::write(directory.join(DATASET_CSV), CSV).unwrap();
+
+        let chunked = format!("{:x}\r\n{}\r\n0\r\n\r\n", MOVIE.len(), MOVIE);
+        let mut warc = Vec::new();
+
+        for (uri, http) in [
+            (
+                "https://en.wikipedia.org/wiki/Jurassic",
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}",
+                    JURASSIC.len(),
+                    JURASSIC
+                ),
+            ),
+            (
+                "https://www.imdb.com/title/tt0111161/?ref_=chttp_t_1",
+                format!("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{chunked}"),
+            ),
+        ] {
+            write!(
+                warc,
+                "WARC/1.0\r\nWARC-Type: response\r\nWARC-Target-URI: <{uri}>\r\nContent-Length: {}\r\n\r\n{http}\r\n\r\n",
+                http.len()
+            )
+            .unwrap();
+        }
+
+        fs::write(&path, &warc).unwrap();
+        assert_entries(&path);
+
+        let truncated = directory.join("truncated.warc");
+        fs::write(&truncated, &warc[..warc.len() - 40]).unwrap();
+        let error = Archive::open(&truncated).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("WARC record truncated"));
+    }
+
+    #[test]
+    fn test_dechunk() {
+        assert_eq!(
+            dechunk(b"3\r\nabc\r\n2;x=y\r\nde\r\n0\r\n\r\n").unwrap(),
+            b"abcde"
+        );
+        let error = dechunk(b"a\r\nabc").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Truncated chunk");
+    }
+}
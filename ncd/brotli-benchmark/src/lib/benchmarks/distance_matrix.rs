@@ -1,55 +1,86 @@
 use plotly::{
-    common::{AxisSide, Title},
+    common::{AxisSide, Marker, Mode, Title},
     layout::Axis,
-    HeatMap, Layout, Plot,
+    HeatMap, Layout, Plot, Scatter,
 };
-use rayon::prelude::*;
+use std::collections::BTreeSet;
+use std::sync::atomic::AtomicBool;
 
 use crate::benchmarks::get_dataset_path;
+use crate::configuration::Configuration;
 use crate::{benchmarks::Cache, dataset};
-use core::calculate;
+use core::matrix::{CondensedMatrix, Progress};
 
-pub fn heatmap(cache: &mut Cache, dataset_name: &str) {
+pub fn heatmap(cache: &mut Cache, configuration: &Configuration, dataset_name: &str) {
     let dataset =
         dataset::Dataset::new(get_dataset_path(dataset_name)).expect("Failed to load dataset");
+    heatmap_plot(cache, configuration, dataset_name, &dataset).show();
+}
+
+pub fn heatmap_plot(
+    cache: &Cache,
+    configuration: &Configuration,
+    dataset_name: &str,
+    dataset: &dataset::Dataset,
+) -> Plot {
     let entries = dataset.entries();
+    let page_types = dataset.page_types();
+    let documents = configuration.documents(entries);
     let page_names = entries
         .iter()
-        .map(|entry| entry.get_name())
+        .map(|entry| {
+            format!(
+                "<span style=\"color:{}\">{}</span>",
+                page_types.get(&entry.page_type).color,
+                entry.get_name(page_types)
+            )
+        })
         .collect::<Vec<String>>();
 
+    let never = AtomicBool::new(false);
+    let distances = CondensedMatrix::compute(
+        entries.len(),
+        |a, b| cache.calculate_documents(configuration, &documents, a, b),
+        |Progress { completed, total }| {
+            if completed.is_multiple_of((total / 100).max(1)) || completed == total {
+                println!("{dataset_name}: calculated {completed}/{total} distances");
+            }
+        },
+        &never,
+    )
+    .expect("Distance matrix computation is never cancelled");
+
     let heatmap = HeatMap::new(
         page_names.clone(),
         page_names.clone(),
-        entries
-            .par_iter()
-            .map(|entry_a| {
-                entries
-                    .iter()
-                    .map(|entry_b| {
-                        println!(
-                            "Calculating distance between {} and {}",
-                            entry_a.url, entry_b.url
-                        );
-                        cache.calculate(
-                            &entry_a.get_content().unwrap(),
-                            &entry_b.get_content().unwrap(),
-                        )
-                    })
-                    .collect::<Vec<f64>>()
-            })
-            .collect::<Vec<Vec<f64>>>(),
+        distances.to_square(),
     );
 
     let mut plot = Plot::new();
     plot.add_trace(heatmap);
 
+    for name in entries
+        .iter()
+        .map(|entry| &entry.page_type)
+        .collect::<BTreeSet<_>>()
+    {
+        let page_type = page_types.get(name);
+        plot.add_trace(
+            Scatter::new(Vec::<String>::new(), Vec::<String>::new())
+                .name(format!("{} {}", page_type.icon, page_type.label))
+                .mode(Mode::Markers)
+                .marker(Marker::new().color(page_type.color)),
+        );
+    }
+
     let layout = Layout::new()
         .title(format!(
-            "Normalized Compression Distance for {}",
+            "Compression distance ({}) for {}",
+            configuration.distance.name(),
             dataset_name
         ))
         .width(800)
+        .show_legend(true)
         .height(800)
         .x_axis(
             Axis::new()
@@ -67,6 +98,24 @@ pub fn heatmap(cache: &mut Cache, dataset_name: &str) {
                 .tick_text(page_names.clone()),
         );
     plot.set_layout(layout);
+    plot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic;
+    use core::distance::Distance;
+
+    #[test]
+    fn test_heatmap_plot() {
+        let (_temp, dataset) = synthetic::temp_dataset(1, 2);
+        let mut configuration = Configuration::recommended();
+        let plot = heatmap_plot(&Cache::new(), &configuration, "synthetic", &dataset).to_json();
+        assert!(plot.contains("Compression distance (ncd) for synthetic"));
 
-    plot.show();
+        configuration.distance = Distance::Cdm;
+        let plot = heatmap_plot(&Cache::new(), &configuration, "synthetic", &dataset).to_json();
+        assert!(plot.contains("Compression distance (cdm) for synthetic"));
+    }
 }
@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+
+use crate::benchmarks::get_dataset_path;
+use crate::configuration::Configuration;
+use crate::{benchmarks::Cache, dataset};
+use core::lsh::Prefilter;
+
+#[derive(Debug, Clone)]
+pub struct PrefilterReport {
+    pub dataset: String,
+    pub max_distance: f64,
+    pub pairs: usize,
+    pub candidates: usize,
+    pub relevant: usize,
+    pub true_positives: usize,
+}
+
+impl PrefilterReport {
+    pub fn recall(&self) -> f64 {
+        if self.relevant == 0 {
+            return 1.0;
+        }
+        self.true_positives as f64 / self.relevant as f64
+    }
+
+    pub fn precision(&self) -> f64 {
+        if self.candidates == 0 {
+            return 1.0;
+        }
+        self.true_positives as f64 / self.candidates as f64
+    }
+}
+
+pub fn lsh_prefilter(
+    cache: &mut Cache,
+    configuration: &Configuration,
+    dataset_name: &str,
+    prefilter: &Prefilter,
+    max_distance: f64,
+) -> PrefilterReport {
+    let dataset =
+        dataset::Dataset::new(get_dataset_path(dataset_name)).expect("Failed to load dataset");
+    let entries = dataset.entries();
+    let documents = configuration.documents(entries);
+    let skeletons = (0..entries.len())
+        .map(|index| documents.get(index).unwrap().to_string())
+        .collect::<Vec<String>>();
+
+    let candidates = prefilter.candidate_pairs(&skeletons);
+    let mut relevant = BTreeSet::new();
+
+    for a in 0..entries.len() {
+        for b in a + 1..entries.len() {
+            if cache.calculate_documents(configuration, &documents, a, b) <= max_distance {
+                relevant.insert((a, b));
+            }
+        }
+    }
+
+    let report = PrefilterReport {
+        dataset: dataset_name.to_string(),
+        max_distance,
+        pairs: entries.len() * entries.len().saturating_sub(1) / 2,
+        candidates: candidates.len(),
+        relevant: relevant.len(),
+        true_positives: prefilter
+            .candidate_distances(
+                &skeletons,
+                &*configuration.compressor,
+                configuration.distance,
+            )
+            .into_iter()
+            .filter(|&(_, _, distance)| distance <= max_distance)
+            .count(),
+    };
+
+    println!(
+        "LSH prefilter for {}: {} of {} pairs are candidates, recall {:.3}, precision {:.3} (NCD <= {})",
+        report.dataset,
+        report.candidates,
+        report.pairs,
+        report.recall(),
+        report.precision(),
+        report.max_distance
+    );
+
+    report
+}
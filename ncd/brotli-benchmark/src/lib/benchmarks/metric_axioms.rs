@@ -0,0 +1,273 @@
+use serde::Serialize;
+
+use super::get_dataset_path;
+use crate::configuration::Configuration;
+use crate::{dataset, distances};
+use core::distance::{CompressedSizes, Distance};
+
+const WORST_VIOLATIONS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Tolerance {
+    pub identity: f64,
+    pub symmetry: f64,
+    pub triangle: f64,
+}
+
+impl Tolerance {
+    pub fn new(identity: f64, symmetry: f64, triangle: f64) -> Self {
+        Self {
+            identity,
+            symmetry,
+            triangle,
+        }
+    }
+
+    pub fn recommended() -> Self {
+        Self::new(0.01, 0.01, 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub amount: f64,
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AxiomReport {
+    pub checked: usize,
+    pub violations: usize,
+    pub worst: Vec<Violation>,
+}
+
+impl AxiomReport {
+    fn check(&mut self, amount: f64, tolerance: f64, urls: impl FnOnce() -> Vec<String>) {
+        self.checked += 1;
+
+        if amount <= tolerance {
+            return;
+        }
+
+        self.violations += 1;
+
+        if self.worst.len() == WORST_VIOLATIONS
+            && self
+                .worst
+                .last()
+                .is_some_and(|worst| worst.amount >= amount)
+        {
+            return;
+        }
+
+        let position = self.worst.partition_point(|worst| worst.amount >= amount);
+        self.worst.insert(
+            position,
+            Violation {
+                amount,
+                urls: urls(),
+            },
+        );
+        self.worst.truncate(WORST_VIOLATIONS);
+    }
+
+    pub fn ratio(&self) -> f64 {
+        if self.checked == 0 {
+            return 0.0;
+        }
+
+        self.violations as f64 / self.checked as f64
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricReport {
+    pub dataset: String,
+    pub configuration: String,
+    pub tolerance: Tolerance,
+    pub identity: AxiomReport,
+    pub symmetry: AxiomReport,
+    pub triangle: AxiomReport,
+}
+
+impl MetricReport {
+    // Distances are symmetric by construction, since they use the smaller of both
+    // concatenation orders, so symmetry is measured on the raw sizes instead: how far the
+    // distance would move if only one order were compressed.
+    pub fn new(
+        dataset: &str,
+        configuration: &str,
+        urls: &[String],
+        distance: Distance,
+        sizes: &[Vec<CompressedSizes>],
+        tolerance: Tolerance,
+    ) -> Self {
+        let mut report = Self {
+            dataset: dataset.to_string(),
+            configuration: configuration.to_string(),
+            tolerance,
+            identity: AxiomReport::default(),
+            symmetry: AxiomReport::default(),
+            triangle: AxiomReport::default(),
+        };
+        let distances = distances::matrix(distance, sizes);
+        let n = urls.len();
+
+        for a in 0..n {
+            report
+                .identity
+                .check(distances[a][a].abs(), tolerance.identity, || {
+                    vec![urls[a].clone()]
+                });
+
+            for b in a + 1..n {
+                report
+                    .symmetry
+                    .check(asymmetry(&sizes[a][b]), tolerance.symmetry, || {
+                        vec![urls[a].clone(), urls[b].clone()]
+                    });
+            }
+        }
+
+        for a in 0..n {
+            for b in a + 1..n {
+                for c in b + 1..n {
+                    for (x, y, z) in [(a, b, c), (b, a, c), (a, c, b)] {
+                        let amount = distances[x][z] - distances[x][y] - distances[y][z];
+                        report.triangle.check(amount, tolerance.triangle, || {
+                            vec![urls[x].clone(), urls[y].clone(), urls[z].clone()]
+                        });
+                    }
+                }
+            }
+        }
+
+        report
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Metric axioms for {} with {}:",
+            self.dataset, self.configuration
+        );
+
+        for (name, axiom) in [
+            ("identity", &self.identity),
+            ("symmetry", &self.symmetry),
+            ("triangle", &self.triangle),
+        ] {
+            println!(
+                "  {name}: {} of {} checks violated ({:.2}%)",
+                axiom.violations,
+                axiom.checked,
+                axiom.ratio() * 100.0
+            );
+
+            if let Some(worst) = axiom.worst.first() {
+                println!(
+                    "    worst by {:.6}: {}",
+                    worst.amount,
+                    worst.urls.join(", ")
+                );
+            }
+        }
+    }
+}
+
+fn asymmetry(sizes: &CompressedSizes) -> f64 {
+    if sizes.max() == 0 {
+        return 0.0;
+    }
+
+    sizes.ab.abs_diff(sizes.ba) as f64 / sizes.max() as f64
+}
+
+pub fn metric_axioms(
+    dataset_name: &str,
+    configuration: &Configuration,
+    tolerance: Tolerance,
+) -> MetricReport {
+    let dataset =
+        dataset::Dataset::new(get_dataset_path(dataset_name)).expect("Failed to load dataset");
+    let entries = dataset.entries();
+    let urls = entries
+        .iter()
+        .map(|entry| entry.url.clone())
+        .collect::<Vec<String>>();
+    let sizes = configuration.document_sizes_matrix(&configuration.documents(entries));
+
+    let report = MetricReport::new(
+        dataset_name,
+        &configuration.name,
+        &urls,
+        configuration.distance,
+        &sizes,
+        tolerance,
+    );
+    report.print();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic::{Generator, Template};
+    use core::calculate;
+
+    #[test]
+    fn test_triangle_inequality() {
+        let mut generator = Generator::recommended(7);
+        let page_a = generator.page(Template::Article, 0).html;
+        let page_b = generator.page(Template::Video, 0).html;
+        let page_c = generator.page(Template::Video, 1).html;
+        let d_ac = calculate(&page_a, &page_c);
+        let d_ab = calculate(&page_a, &page_b);
+        let d_bc = calculate(&page_b, &page_c);
+        println!("d_ab = {d_ab}, d_ac = {d_ac}, d_bc = {d_bc}");
+        assert!(d_ac + d_bc >= d_ab);
+    }
+
+    #[test]
+    fn test_metric_report() {
+        let urls = ["a", "b", "c"]
+            .iter()
+            .map(|url| url.to_string())
+            .collect::<Vec<String>>();
+        // Pages of 1000 bytes, so the NCD of a pair is (combined - 1000) / 1000.
+        let combined = [
+            [(1000, 1000), (1100, 1100), (1500, 1500)],
+            [(1100, 1100), (1005, 1005), (1200, 1300)],
+            [(1500, 1500), (1300, 1200), (1020, 1020)],
+        ];
+        let sizes = combined
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&(ab, ba)| CompressedSizes {
+                        a: 1000,
+                        b: 1000,
+                        ab,
+                        ba,
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<CompressedSizes>>>();
+
+        let report = MetricReport::new(
+            "test",
+            "test",
+            &urls,
+            Distance::Ncd,
+            &sizes,
+            Tolerance::recommended(),
+        );
+        assert_eq!(report.identity.checked, 3);
+        assert_eq!(report.identity.violations, 1);
+        assert_eq!(report.identity.worst[0].urls, ["c"]);
+        assert_eq!(report.symmetry.violations, 1);
+        assert_eq!(report.symmetry.worst[0].urls, ["b", "c"]);
+        assert_eq!(report.triangle.checked, 3);
+        assert_eq!(report.triangle.violations, 1);
+        assert_eq!(report.triangle.worst[0].urls, ["a", "b", "c"]);
+    }
+}
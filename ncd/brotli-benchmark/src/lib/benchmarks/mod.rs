@@ -1,16 +1,18 @@
+use crate::configuration::Configuration;
+use crate::document_store::DocumentStore;
 use core::calculate;
 use std::{
     collections::BTreeMap,
-    hash,
     path::PathBuf,
     sync::{Arc, RwLock},
 };
 
 pub mod distance_matrix;
-pub mod triangle_inequality;
+pub mod lsh_prefilter;
+pub mod metric_axioms;
 
 pub struct Cache {
-    cache: Arc<RwLock<BTreeMap<(u64, u64), f64>>>,
+    cache: Arc<RwLock<BTreeMap<(String, String), f64>>>,
 }
 
 impl Cache {
@@ -20,18 +22,57 @@ impl Cache {
         }
     }
 
-    fn hash_string(s: &str) -> u64 {
+    fn hash_string(s: &str) -> String {
         use std::hash::{Hash, Hasher};
 
         let mut hasher = std::hash::DefaultHasher::new();
         s.hash(&mut hasher);
-        hasher.finish()
+        format!("{:016x}", hasher.finish())
     }
 
     pub fn calculate(&self, page_a: &str, page_b: &str) -> f64 {
-        let hash_a = Self::hash_string(page_a);
-        let hash_b = Self::hash_string(page_b);
+        self.get_or_calculate(Self::hash_string(page_a), Self::hash_string(page_b), || {
+            calculate(page_a, page_b)
+        })
+    }
+
+    // Keys include the configuration name, so distances of different strippers or
+    // compressors never collide. Documents are only loaded on a cache miss, unless the entry
+    // has no recorded hash and the skeleton itself has to be hashed.
+    pub fn calculate_documents(
+        &self,
+        configuration: &Configuration,
+        documents: &DocumentStore,
+        a: usize,
+        b: usize,
+    ) -> f64 {
+        assert!(
+            configuration.encoding.is_none(),
+            "Tokenized configurations need an alphabet of the whole corpus"
+        );
+        let key = |index: usize| {
+            let hash = documents.entries()[index]
+                .sha256
+                .clone()
+                .unwrap_or_else(|| Self::hash_string(&documents.get(index).unwrap()));
+            format!("{}:{hash}", configuration.name)
+        };
+
+        self.get_or_calculate(key(a), key(b), || {
+            let sizes = configuration.compressor.get_sizes(
+                documents.get(a).unwrap().as_bytes(),
+                documents.get(b).unwrap().as_bytes(),
+            );
+            configuration.distance.compute(&sizes)
+        })
+    }
 
+    fn get_or_calculate(
+        &self,
+        hash_a: String,
+        hash_b: String,
+        calculate: impl FnOnce() -> f64,
+    ) -> f64 {
         let key = if hash_a <= hash_b {
             (hash_a, hash_b)
         } else {
@@ -45,7 +86,7 @@ impl Cache {
             }
         }
 
-        let result = calculate(&page_a, &page_b);
+        let result = calculate();
         let mut write_guard = self.cache.write().unwrap();
         use std::collections::btree_map::Entry;
 
@@ -59,9 +100,53 @@ impl Cache {
     }
 }
 
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub const DATASETS: [&str; 4] = ["euronews.com", "amazon", "imdb", "wikipedia"];
+
 pub fn get_dataset_path(directory: &str) -> PathBuf {
     let project_root = env!("CARGO_MANIFEST_DIR");
     std::path::Path::new(project_root)
         .join("../../dataset")
         .join(directory)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::{Backend, Stripper};
+    use crate::synthetic;
+    use core::compress::lz::Variant;
+
+    #[test]
+    fn test_cache_per_configuration() {
+        let (_temp, dataset) = synthetic::temp_dataset(4, 1);
+        let cache = Cache::new();
+
+        let brotli = Configuration::recommended();
+        let lz = Configuration::from_parts(
+            Stripper::FilterAttributes,
+            Backend::Lz {
+                variant: Variant::Lz78,
+            },
+        );
+
+        for configuration in [&brotli, &lz] {
+            let documents = configuration.documents(dataset.entries());
+            let sizes = configuration.compressor.get_sizes(
+                documents.get(0).unwrap().as_bytes(),
+                documents.get(1).unwrap().as_bytes(),
+            );
+            assert_eq!(
+                cache.calculate_documents(configuration, &documents, 0, 1),
+                configuration.distance.compute(&sizes)
+            );
+        }
+
+        assert_eq!(cache.cache.read().unwrap().len(), 2);
+    }
+}
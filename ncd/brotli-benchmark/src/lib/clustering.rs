@@ -0,0 +1,61 @@
+use core::matrix::CondensedMatrix;
+
+pub fn average_linkage(distances: &CondensedMatrix, clusters: usize) -> Vec<usize> {
+    let mut members = (0..distances.size())
+        .map(|i| vec![i])
+        .collect::<Vec<Vec<usize>>>();
+
+    while members.len() > clusters.max(1) {
+        let mut closest = (0, 1, f64::INFINITY);
+
+        for a in 0..members.len() {
+            for b in a + 1..members.len() {
+                let distance = linkage(distances, &members[a], &members[b]);
+
+                if distance < closest.2 {
+                    closest = (a, b, distance);
+                }
+            }
+        }
+
+        let merged = members.remove(closest.1);
+        members[closest.0].extend(merged);
+    }
+
+    let mut assignments = vec![0; distances.size()];
+
+    for (cluster, indices) in members.iter().enumerate() {
+        for &index in indices {
+            assignments[index] = cluster;
+        }
+    }
+
+    assignments
+}
+
+fn linkage(distances: &CondensedMatrix, a: &[usize], b: &[usize]) -> f64 {
+    let total = a
+        .iter()
+        .flat_map(|&i| b.iter().map(move |&j| distances.get(i, j)))
+        .sum::<f64>();
+    total / (a.len() * b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_linkage() {
+        let distances = vec![
+            vec![0.0, 0.1, 0.9, 0.8],
+            vec![0.1, 0.0, 0.8, 0.9],
+            vec![0.9, 0.8, 0.0, 0.2],
+            vec![0.8, 0.9, 0.2, 0.0],
+        ];
+        let assignments = average_linkage(&CondensedMatrix::from_square(&distances), 2);
+        assert_eq!(assignments[0], assignments[1]);
+        assert_eq!(assignments[2], assignments[3]);
+        assert_ne!(assignments[0], assignments[2]);
+    }
+}
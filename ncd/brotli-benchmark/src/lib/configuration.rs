@@ -0,0 +1,361 @@
+use core::alphabet::{Alphabet, Encoding};
+use core::compress::lz::{CompressLz, Variant};
+use core::compress::ppm::CompressPpm;
+use core::compress::{brotli::CompressBrotli, Compressor};
+use core::distance::{CompressedSizes, Distance, DistanceReport};
+use core::matrix::CondensedMatrix;
+use core::strip::{filter_attributes::FilterAttributes, strip_content::StripContent, StripHtml};
+use rayon::prelude::*;
+use std::io;
+use std::path::Path;
+
+use crate::dataset::Entry;
+use crate::document_store::DocumentStore;
+use crate::manifest::sha256_hex;
+use crate::matrix_store::{MatrixStore, Update};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stripper {
+    FilterAttributes,
+    StripContent,
+}
+
+impl Stripper {
+    pub fn all() -> Vec<Self> {
+        vec![Self::FilterAttributes, Self::StripContent]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FilterAttributes => "filter_attributes",
+            Self::StripContent => "strip_content",
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn StripHtml + Sync> {
+        match self {
+            Self::FilterAttributes => Box::new(FilterAttributes {}),
+            Self::StripContent => Box::new(StripContent {}),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Brotli { quality: u32, lg_window_size: u32 },
+    Lz { variant: Variant },
+    Ppm { order: usize },
+}
+
+impl Backend {
+    pub fn recommended() -> Self {
+        let brotli = CompressBrotli::recommended();
+        Self::Brotli {
+            quality: brotli.quality(),
+            lg_window_size: brotli.lg_window_size(),
+        }
+    }
+
+    pub fn family(&self) -> &'static str {
+        match self {
+            Self::Brotli { .. } => "brotli",
+            Self::Lz { .. } => "lz",
+            Self::Ppm { .. } => "ppm",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Brotli {
+                quality,
+                lg_window_size,
+            } => format!("brotli(q={quality},w={lg_window_size})"),
+            Self::Lz {
+                variant: Variant::Lz76,
+            } => "lz76".to_string(),
+            Self::Lz {
+                variant: Variant::Lz78,
+            } => "lz78".to_string(),
+            Self::Ppm { order } => format!("ppm(k={order})"),
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn Compressor + Sync> {
+        match *self {
+            Self::Brotli {
+                quality,
+                lg_window_size,
+            } => Box::new(CompressBrotli::new(quality, lg_window_size)),
+            Self::Lz { variant } => Box::new(CompressLz::new(variant)),
+            Self::Ppm { order } => Box::new(CompressPpm::new(order)),
+        }
+    }
+}
+
+pub struct Configuration {
+    pub name: String,
+    pub stripper: Box<dyn StripHtml + Sync>,
+    pub compressor: Box<dyn Compressor + Sync>,
+    pub distance: Distance,
+    pub encoding: Option<Encoding>,
+}
+
+impl Configuration {
+    pub fn new(
+        name: &str,
+        stripper: Box<dyn StripHtml + Sync>,
+        compressor: Box<dyn Compressor + Sync>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            stripper,
+            compressor,
+            distance: Distance::Ncd,
+            encoding: None,
+        }
+    }
+
+    pub fn with_distance(mut self, distance: Distance) -> Self {
+        if distance != self.distance {
+            self.name = format!("{}/{}", self.name, distance.name());
+            self.distance = distance;
+        }
+
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.name = format!("{}/{}", self.name, encoding.name());
+        self.encoding = Some(encoding);
+        self
+    }
+
+    pub fn from_parts(stripper: Stripper, backend: Backend) -> Self {
+        Self::new(
+            &format!("{}/{}", stripper.name(), backend.name()),
+            stripper.build(),
+            backend.build(),
+        )
+    }
+
+    pub fn recommended() -> Self {
+        Self::from_parts(Stripper::FilterAttributes, Backend::recommended())
+    }
+
+    pub fn all() -> Vec<Self> {
+        Stripper::all()
+            .into_iter()
+            .map(|stripper| Self::from_parts(stripper, Backend::recommended()))
+            .collect()
+    }
+
+    pub fn tokenized() -> Vec<Self> {
+        Stripper::all()
+            .into_iter()
+            .map(|stripper| {
+                Self::from_parts(stripper, Backend::recommended()).with_encoding(Encoding::Varint)
+            })
+            .collect()
+    }
+
+    pub fn skeleton(&self, page: &str) -> String {
+        self.stripper.strip_html(page)
+    }
+
+    // The alphabet tokenized configurations share across a corpus; `None` for plain bytes.
+    pub fn alphabet(
+        &self,
+        skeletons: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Option<Alphabet> {
+        self.encoding
+            .map(|encoding| Alphabet::new(encoding, skeletons))
+    }
+
+    // Skeletons as compressor input, tokenized with an alphabet shared by all of them.
+    pub fn inputs(&self, skeletons: Vec<String>) -> Vec<Vec<u8>> {
+        match self.alphabet(&skeletons) {
+            Some(alphabet) => skeletons
+                .par_iter()
+                .map(|skeleton| alphabet.encode(skeleton))
+                .collect(),
+            None => skeletons.into_iter().map(String::into_bytes).collect(),
+        }
+    }
+
+    // `alphabet` comes from `alphabet` over the whole corpus, since one built from two pages
+    // would not match the symbols of a distance matrix.
+    pub fn distance_report(
+        &self,
+        alphabet: Option<&Alphabet>,
+        page_a: &str,
+        page_b: &str,
+    ) -> DistanceReport {
+        assert_eq!(
+            alphabet.map(Alphabet::encoding),
+            self.encoding,
+            "The alphabet does not match the encoding of {}",
+            self.name
+        );
+        let [input_a, input_b] = [page_a, page_b].map(|page| {
+            let skeleton = self.skeleton(page);
+            match alphabet {
+                Some(alphabet) => alphabet.encode(&skeleton),
+                None => skeleton.into_bytes(),
+            }
+        });
+        self.distance.report(&*self.compressor, &input_a, &input_b)
+    }
+
+    pub fn page_inputs(&self, pages: &[String]) -> Vec<Vec<u8>> {
+        let skeletons = pages
+            .par_iter()
+            .map(|page| self.skeleton(page))
+            .collect::<Vec<String>>();
+
+        self.inputs(skeletons)
+    }
+
+    // Sizes are measured in both orders, so every distance is symmetric and each pair is
+    // compressed once.
+    pub fn distance_matrix(&self, inputs: &[Vec<u8>]) -> CondensedMatrix {
+        CondensedMatrix::from_fn(inputs.len(), |a, b| {
+            self.distance
+                .compute(&self.compressor.get_sizes(&inputs[a], &inputs[b]))
+        })
+    }
+
+    // Distance of every input to itself, which the condensed matrix does not store.
+    pub fn self_distances(&self, inputs: &[Vec<u8>]) -> Vec<f64> {
+        inputs
+            .par_iter()
+            .map(|input| {
+                self.distance
+                    .compute(&self.compressor.get_sizes(input, input))
+            })
+            .collect()
+    }
+
+    pub fn documents<'a>(&'a self, entries: &'a [Entry]) -> DocumentStore<'a> {
+        DocumentStore::recommended(entries, &*self.stripper)
+    }
+
+    // Documents tokenized with the alphabet of the whole store, or `None` when the
+    // configuration compresses skeletons as they are.
+    fn encode_documents(&self, documents: &DocumentStore) -> Option<Vec<Vec<u8>>> {
+        let alphabet =
+            self.alphabet((0..documents.len()).map(|index| documents.get(index).unwrap()))?;
+        Some(
+            (0..documents.len())
+                .into_par_iter()
+                .map(|index| alphabet.encode(&documents.get(index).unwrap()))
+                .collect(),
+        )
+    }
+
+    fn document_sizes(
+        &self,
+        documents: &DocumentStore,
+        encoded: Option<&[Vec<u8>]>,
+        a: usize,
+        b: usize,
+    ) -> CompressedSizes {
+        match encoded {
+            Some(encoded) => self.compressor.get_sizes(&encoded[a], &encoded[b]),
+            None => self.compressor.get_sizes(
+                documents.get(a).unwrap().as_bytes(),
+                documents.get(b).unwrap().as_bytes(),
+            ),
+        }
+    }
+
+    // Every ordered pair including the diagonal, for checks of the distance itself.
+    pub fn document_sizes_matrix(&self, documents: &DocumentStore) -> Vec<Vec<CompressedSizes>> {
+        let encoded = self.encode_documents(documents);
+
+        (0..documents.len())
+            .into_par_iter()
+            .map(|a| {
+                (0..documents.len())
+                    .map(|b| self.document_sizes(documents, encoded.as_deref(), a, b))
+                    .collect::<Vec<CompressedSizes>>()
+            })
+            .collect()
+    }
+
+    // Sizes are measured in both orders, so every distance is symmetric and each pair is
+    // compressed once.
+    pub fn document_distance_matrix(&self, documents: &DocumentStore) -> CondensedMatrix {
+        let encoded = self.encode_documents(documents);
+
+        CondensedMatrix::from_fn(documents.len(), |a, b| {
+            self.distance
+                .compute(&self.document_sizes(documents, encoded.as_deref(), a, b))
+        })
+    }
+
+    // Reuses the distances stored at `path` for documents whose skeleton did not change and
+    // saves the updated matrix back.
+    pub fn incremental_distance_matrix(
+        &self,
+        documents: &DocumentStore,
+        path: &Path,
+    ) -> io::Result<(CondensedMatrix, Update)> {
+        let keys = (0..documents.len())
+            .into_par_iter()
+            .map(|index| {
+                let skeleton = documents.get(index)?;
+                Ok(format!(
+                    "{} {}",
+                    documents.entries()[index].url,
+                    sha256_hex(skeleton.as_bytes())
+                ))
+            })
+            .collect::<io::Result<Vec<String>>>()?;
+
+        // The shared alphabet changes with the corpus, so tokenized distances are never reused.
+        let encoded = self.encode_documents(documents);
+        let mut store = match encoded {
+            Some(_) => MatrixStore::new(&self.name),
+            None => MatrixStore::load_or_new(path, &self.name)?,
+        };
+
+        let update = store.update(&keys, |a, b| {
+            self.distance
+                .compute(&self.document_sizes(documents, encoded.as_deref(), a, b))
+        });
+        store.save(path)?;
+
+        Ok((store.matrix, update))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic::Generator;
+
+    #[test]
+    fn test_distance_matrix() {
+        let configuration = Configuration::recommended();
+        let pages = Generator::recommended(3)
+            .generate(1)
+            .into_iter()
+            .map(|page| page.html)
+            .collect::<Vec<String>>();
+        let inputs = configuration.page_inputs(&pages);
+        let distance = |a: &[u8], b: &[u8]| {
+            configuration
+                .distance
+                .compute(&configuration.compressor.get_sizes(a, b))
+        };
+
+        let matrix = configuration.distance_matrix(&inputs);
+        assert_eq!(matrix.size(), 4);
+        assert_eq!(matrix.get(1, 3), distance(&inputs[1], &inputs[3]));
+        assert_eq!(matrix.get(3, 1), distance(&inputs[1], &inputs[3]));
+
+        let self_distances = configuration.self_distances(&inputs);
+        assert_eq!(self_distances.len(), 4);
+        assert_eq!(self_distances[2], distance(&inputs[2], &inputs[2]));
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+enum State {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    QuoteInQuoted,
+    AfterQuoted,
+}
+
+pub fn parse(text: &str) -> Result<Vec<Record>, ParseError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut state = State::FieldStart;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            continue;
+        }
+
+        match state {
+            State::FieldStart => match c {
+                '"' => state = State::Quoted,
+                ',' => fields.push(String::new()),
+                '\n' => {
+                    fields.push(String::new());
+                    push_record(&mut records, &mut fields, record_line);
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    field.push(c);
+                    state = State::Unquoted;
+                }
+            },
+            State::Unquoted => match c {
+                ',' => {
+                    fields.push(field.trim_end().to_string());
+                    field.clear();
+                    state = State::FieldStart;
+                }
+                '\n' => {
+                    fields.push(field.trim_end().to_string());
+                    field.clear();
+                    push_record(&mut records, &mut fields, record_line);
+                    state = State::FieldStart;
+                }
+                '"' => {
+                    return Err(ParseError {
+                        line,
+                        message: "Unexpected quote in unquoted field".to_string(),
+                    })
+                }
+                c => field.push(c),
+            },
+            State::Quoted => match c {
+                '"' => state = State::QuoteInQuoted,
+                c => field.push(c),
+            },
+            State::QuoteInQuoted => match c {
+                '"' => {
+                    field.push('"');
+                    state = State::Quoted;
+                }
+                ',' => {
+                    fields.push(std::mem::take(&mut field));
+                    state = State::FieldStart;
+                }
+                '\n' => {
+                    fields.push(std::mem::take(&mut field));
+                    push_record(&mut records, &mut fields, record_line);
+                    state = State::FieldStart;
+                }
+                c if c.is_whitespace() => state = State::AfterQuoted,
+                c => {
+                    return Err(ParseError {
+                        line,
+                        message: format!("Unexpected character '{c}' after closing quote"),
+                    })
+                }
+            },
+            State::AfterQuoted => match c {
+                ',' => {
+                    fields.push(std::mem::take(&mut field));
+                    state = State::FieldStart;
+                }
+                '\n' => {
+                    fields.push(std::mem::take(&mut field));
+                    push_record(&mut records, &mut fields, record_line);
+                    state = State::FieldStart;
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    return Err(ParseError {
+                        line,
+                        message: format!("Unexpected character '{c}' after closing quote"),
+                    })
+                }
+            },
+        }
+
+        if c == '\n' {
+            line += 1;
+
+            if matches!(state, State::FieldStart) && fields.is_empty() {
+                record_line = line;
+            }
+        }
+    }
+
+    match state {
+        State::Quoted => {
+            return Err(ParseError {
+                line: record_line,
+                message: "Unterminated quoted field".to_string(),
+            })
+        }
+        State::FieldStart if fields.is_empty() => {}
+        State::FieldStart => {
+            fields.push(String::new());
+            push_record(&mut records, &mut fields, record_line);
+        }
+        State::Unquoted => {
+            fields.push(field.trim_end().to_string());
+            push_record(&mut records, &mut fields, record_line);
+        }
+        State::QuoteInQuoted | State::AfterQuoted => {
+            fields.push(field);
+            push_record(&mut records, &mut fields, record_line);
+        }
+    }
+
+    Ok(records)
+}
+
+fn push_record(records: &mut Vec<Record>, fields: &mut Vec<String>, line: usize) {
+    let fields = std::mem::take(fields);
+
+    if fields.len() == 1 && fields[0].is_empty() {
+        return;
+    }
+
+    records.push(Record { line, fields });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = "url, type\r\n\"https://example.com/a,b\", \"article\"\n\nhttps://example.com/c,\"say \"\"hi\"\"\nthere\"\nlast,";
+        let records = parse(text).unwrap();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].fields, ["url", "type"]);
+        assert_eq!(records[1].line, 2);
+        assert_eq!(records[1].fields, ["https://example.com/a,b", "article"]);
+        assert_eq!(records[2].line, 4);
+        assert_eq!(
+            records[2].fields,
+            ["https://example.com/c", "say \"hi\"\nthere"]
+        );
+        assert_eq!(records[3].line, 6);
+        assert_eq!(records[3].fields, ["last", ""]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("url\n\"open").unwrap_err().line, 2);
+        assert_eq!(parse("url\n\"a\"b").unwrap_err().line, 2);
+        assert_eq!(parse("url\nx\"y").unwrap_err().line, 2);
+    }
+}
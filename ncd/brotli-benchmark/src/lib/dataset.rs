@@ -1,20 +1,103 @@
-use std::fs;
-use std::io::{self, BufRead, BufReader};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, fs, io};
+
+use crate::archive::{Archive, Format};
+use crate::csv;
+use crate::manifest::{self, Manifest, ManifestEntry};
+use crate::page_types::{self, Registry};
+use crate::url_path::url_to_path;
+
+const DATASET_CSV: &str = "dataset.csv";
+const COLUMNS: [&str; 7] = [
+    "url",
+    "type",
+    "path",
+    "fetched_at",
+    "site",
+    "split",
+    "notes",
+];
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Invalid { line: usize, message: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{error}"),
+            Error::Invalid { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<csv::ParseError> for Error {
+    fn from(error: csv::ParseError) -> Self {
+        Error::Invalid {
+            line: error.line,
+            message: error.message,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Split {
+    Train,
+    Test,
+}
+
+impl Split {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "train" => Some(Split::Train),
+            "test" => Some(Split::Test),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Entry {
+    pub line: usize,
     pub url: String,
     pub page_type: String,
     pub filepath: PathBuf,
+    pub sha256: Option<String>,
+    pub fetched_at: Option<String>,
+    pub site: Option<String>,
+    pub split: Option<Split>,
+    pub notes: Option<String>,
+    pub archive: Option<Arc<Archive>>,
 }
 
 impl Entry {
     pub fn get_content(&self) -> io::Result<String> {
-        fs::read_to_string(&self.filepath)
+        String::from_utf8(self.get_bytes()?.into_owned())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    // Members kept in memory are borrowed from the archive instead of copied.
+    pub fn get_bytes(&self) -> io::Result<Cow<'_, [u8]>> {
+        match &self.archive {
+            Some(archive) => archive.read(&self.filepath),
+            None => fs::read(&self.filepath).map(Cow::Owned),
+        }
     }
 
-    pub fn get_name(&self) -> String {
+    pub fn get_name(&self, page_types: &Registry) -> String {
         let without_protocol = self.url.split("://").nth(1).unwrap_or_default();
         let without_domain = without_protocol
             .split('/')
@@ -34,115 +117,204 @@ impl Entry {
             .collect::<Vec<String>>()
             .join("/");
 
-        "/".to_owned() + &truncated + " " + &self.icon()
-    }
-
-    fn icon(&self) -> String {
-        let pt = self.page_type.trim().to_lowercase();
-        match pt.as_str() {
-            "article" => "📄".to_string(),
-            "user" | "person" | "profile" => "👤".to_string(),
-            "category" | "section" => "📂".to_string(),
-            "disambiguation" => "🤔".to_string(),
-            "search" => "🔍".to_string(),
-            "product" => "🛍️".to_string(),
-            "store" => "🏬".to_string(),
-            "movie" => "🎬".to_string(),
-            "user_list" => "📋".to_string(),
-            "video" => "📹".to_string(),
-            "news_article" => "📰".to_string(),
-            "tag" => "🏷️".to_string(),
-            "special" => "✨".to_string(),
-            _ => panic!("Unknown page type: {}", pt),
-        }
+        "/".to_owned() + &truncated + " " + &page_types.get(&self.page_type).icon
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Missing {
+    pub line: usize,
+    pub url: String,
+    pub page_type: String,
+    pub filepath: PathBuf,
+}
+
+// A dataset is a directory or an archive. Zip members are read on demand, while tar.zst and
+// WARC archives are decompressed into memory when the dataset is opened, so very large crawls
+// should be extracted or repacked as zip.
 #[derive(Debug, Clone)]
 pub struct Dataset {
     entries: Vec<Entry>,
+    missing: Vec<Missing>,
+    page_types: Registry,
     directory: PathBuf,
+    archive: Option<Arc<Archive>>,
 }
 
 impl Dataset {
-    pub fn new<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self, Error> {
+        let directory = directory.as_ref();
+        let archive = match Format::detect(directory) {
+            Some(_) if directory.is_file() => Some(Arc::new(Archive::open(directory)?)),
+            _ => None,
+        };
+
         let mut dataset = Dataset {
             entries: Vec::new(),
-            directory: PathBuf::from(directory.as_ref()),
+            missing: Vec::new(),
+            page_types: Registry::for_dataset(directory)?,
+            directory: PathBuf::from(directory),
+            archive,
         };
-        dataset.load_csv(dataset.directory.join("dataset.csv"))?;
+
+        if let Some(text) = dataset.read_text(page_types::FILE_NAME)? {
+            dataset.page_types.load_str(&text)?;
+        }
+
+        dataset.load_csv()?;
         Ok(dataset)
     }
 
-    fn load_csv<P: AsRef<Path>>(&mut self, csv_path: P) -> io::Result<()> {
-        let file = fs::File::open(csv_path)?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
+    fn read_text(&self, name: &str) -> Result<Option<String>, Error> {
+        let bytes = match &self.archive {
+            Some(archive) => archive.read_member(name)?.map(Cow::into_owned),
+            None => {
+                let path = self.directory.join(name);
+                path.is_file().then(|| fs::read(path)).transpose()?
+            }
+        };
 
-        lines.next();
+        bytes
+            .map(|bytes| {
+                String::from_utf8(bytes)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error).into())
+            })
+            .transpose()
+    }
 
-        for line in lines {
-            let line = line?;
+    fn exists(&self, path: &Path) -> bool {
+        match &self.archive {
+            Some(archive) => archive.contains(&archive.member(path)),
+            None => path.is_file(),
+        }
+    }
 
-            if let Some((url, page_type)) = self.parse_csv_line(&line) {
-                if let Some(filepath) = self.get_file_path(&url) {
-                    self.entries.push(Entry {
-                        url,
-                        page_type,
-                        filepath,
-                    });
-                }
+    fn load_csv(&mut self) -> Result<(), Error> {
+        let text = self.read_text(DATASET_CSV)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found in {}", DATASET_CSV, self.directory.display()),
+            )
+        })?;
+        let mut records = csv::parse(&text)?.into_iter();
+
+        let header = match records.next() {
+            Some(header) => header,
+            None => return Ok(()),
+        };
+        let columns = self.parse_header(&header)?;
+        let manifest = match self.read_text(manifest::FILE_NAME)? {
+            Some(text) => Manifest::parse(&text)?,
+            None => Manifest::default(),
+        };
+        let recorded_entries = manifest
+            .entries
+            .iter()
+            .map(|entry| (entry.url.as_str(), entry))
+            .collect::<HashMap<&str, &ManifestEntry>>();
+
+        for record in records {
+            if record.fields.len() != header.fields.len() {
+                return Err(Error::Invalid {
+                    line: record.line,
+                    message: format!(
+                        "Expected {} fields, found {}",
+                        header.fields.len(),
+                        record.fields.len()
+                    ),
+                });
             }
-        }
 
-        Ok(())
-    }
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|column| column == name)
+                    .map(|index| record.fields[index].trim())
+                    .filter(|value| !value.is_empty())
+            };
+            let required = |name: &str| {
+                field(name).ok_or_else(|| Error::Invalid {
+                    line: record.line,
+                    message: format!("Missing value for column '{name}'"),
+                })
+            };
 
-    fn parse_csv_line(&self, line: &str) -> Option<(String, String)> {
-        let parts: Vec<&str> = line.split(',').collect();
+            let url = required("url")?.to_string();
+            let page_type = required("type")?.to_string();
+            let split = match field("split") {
+                Some(value) => Some(Split::parse(value).ok_or_else(|| Error::Invalid {
+                    line: record.line,
+                    message: format!("Unknown split '{value}', expected 'train' or 'test'"),
+                })?),
+                None => None,
+            };
+            let recorded = recorded_entries.get(url.as_str()).copied();
+            let expected = self.directory.join(match (field("path"), recorded) {
+                (Some(path), _) => path.to_string(),
+                (None, Some(recorded)) => recorded.path.clone(),
+                (None, None) => url_to_path(&url),
+            });
+            let recorded =
+                recorded.filter(|recorded| expected == self.directory.join(&recorded.path));
 
-        if parts.len() >= 2 {
-            let url = parts[0].trim().trim_matches('"').to_string();
-            let page_type = parts[1].trim().trim_matches('"').to_string();
-            Some((url, page_type))
-        } else {
-            None
+            if self.exists(&expected) {
+                self.entries.push(Entry {
+                    line: record.line,
+                    url,
+                    page_type,
+                    filepath: expected,
+                    sha256: recorded.and_then(|recorded| recorded.sha256.clone()),
+                    fetched_at: field("fetched_at")
+                        .map(str::to_string)
+                        .or_else(|| recorded.and_then(|recorded| recorded.fetched_at.clone())),
+                    site: field("site").map(str::to_string),
+                    split,
+                    notes: field("notes").map(str::to_string),
+                    archive: self.archive.clone(),
+                });
+            } else {
+                self.missing.push(Missing {
+                    line: record.line,
+                    url,
+                    page_type,
+                    filepath: expected,
+                });
+            }
         }
-    }
 
-    fn url_to_filename(&self, url: &str) -> Option<String> {
-        if let Some(path_start) = url.find("://").and_then(|i| url[i + 3..].find('/')) {
-            let path = &url[url.find("://")? + 3 + path_start + 1..];
-            let decoded = urlencoding::decode(path).ok()?;
-            Some(decoded.into_owned())
-        } else {
-            None
-        }
+        Ok(())
     }
 
-    pub fn get_file_path(&self, url: &str) -> Option<PathBuf> {
-        if let Some(page_name) = self.url_to_filename(url) {
-            let extensions = [".txt", ".md", ".html", ".wiki", ""];
-
-            for ext in &extensions {
-                let filename = format!("{}{}", page_name, ext);
-                let filepath = self.directory.join(&filename);
-
-                if filepath.is_dir() {
-                    let with_index = filepath.join("index.html");
+    fn parse_header(&self, header: &csv::Record) -> Result<Vec<String>, Error> {
+        let columns = header
+            .fields
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect::<Vec<String>>();
 
-                    if with_index.exists() {
-                        return Some(with_index);
-                    }
-                }
+        for column in &columns {
+            if !COLUMNS.contains(&column.as_str()) {
+                return Err(Error::Invalid {
+                    line: header.line,
+                    message: format!("Unknown column '{column}'"),
+                });
+            }
+        }
 
-                if filepath.exists() {
-                    return Some(filepath);
-                }
+        for required in ["url", "type"] {
+            if !columns.iter().any(|column| column == required) {
+                return Err(Error::Invalid {
+                    line: header.line,
+                    message: format!("Missing required column '{required}'"),
+                });
             }
         }
 
-        None
+        Ok(columns)
+    }
+
+    pub fn get_file_path(&self, url: &str) -> Option<PathBuf> {
+        Some(self.directory.join(url_to_path(url))).filter(|filepath| self.exists(filepath))
     }
 
     pub fn get_page_type(&self, url: &str) -> Option<&String> {
@@ -156,33 +328,90 @@ impl Dataset {
         &self.entries
     }
 
+    pub fn missing(&self) -> &Vec<Missing> {
+        &self.missing
+    }
+
+    pub fn page_types(&self) -> &Registry {
+        &self.page_types
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
     pub fn get_content(&self, url: &str) -> Option<String> {
         self.entries
             .iter()
             .find(|entry| entry.url == url)
-            .and_then(|entry| fs::read_to_string(&entry.filepath).ok())
+            .and_then(|entry| entry.get_content().ok())
+    }
+
+    pub fn site(&self) -> String {
+        self.directory
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn merge(datasets: Vec<Dataset>) -> Self {
+        let mut merged = Dataset {
+            entries: Vec::new(),
+            missing: Vec::new(),
+            page_types: Registry::default(),
+            directory: PathBuf::new(),
+            archive: None,
+        };
+
+        for (index, dataset) in datasets.into_iter().enumerate() {
+            let site = dataset.site();
+
+            merged.directory = if index == 0 {
+                dataset.directory.clone()
+            } else {
+                merged
+                    .directory
+                    .ancestors()
+                    .find(|ancestor| dataset.directory.starts_with(ancestor))
+                    .unwrap_or(Path::new(""))
+                    .to_path_buf()
+            };
+            merged.page_types.merge(&dataset.page_types);
+            merged.missing.extend(dataset.missing);
+            merged
+                .entries
+                .extend(dataset.entries.into_iter().map(|mut entry| {
+                    entry.site.get_or_insert_with(|| site.clone());
+                    entry
+                }));
+        }
+
+        merged
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use plotters::data;
-
     use super::*;
+    use crate::synthetic;
 
-    fn get_dataset_path(directory: &str) -> PathBuf {
-        let project_root = env!("CARGO_MANIFEST_DIR");
-        let full_path = std::path::Path::new(project_root)
-            .join("../../dataset")
-            .join(directory);
-        full_path
+    fn write_dataset(root: &Path, name: &str, csv: &str) -> PathBuf {
+        let directory = root.join(name);
+        fs::create_dir_all(directory.join("wiki")).unwrap();
+        fs::write(directory.join("wiki/A,B.html"), "<html></html>").unwrap();
+        fs::write(directory.join("page.html"), "<html></html>").unwrap();
+        fs::write(directory.join("dataset.csv"), csv).unwrap();
+        directory
     }
 
     #[test]
     fn test_get_file_index() {
-        let dataset = Dataset::new(get_dataset_path("euronews.com")).unwrap();
+        let (_temp, dataset) = synthetic::temp_dataset(3, 2);
         let entries = dataset.entries();
 
+        assert_eq!(entries.len(), 8);
+        assert!(dataset.missing().is_empty());
+
         for entry in entries {
             assert!(
                 entry.get_content().is_ok(),
@@ -191,4 +420,79 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_optional_columns() {
+        let temp = tempfile::tempdir().unwrap();
+        let directory = write_dataset(
+            temp.path(),
+            "optional-columns",
+            "url, type, path, site, split, notes\n\
+             \"https://en.wikipedia.org/wiki/A,B\", \"article\", , wikipedia, train, \"has, comma\"\n\
+             \"https://example.com/x\", \"special\", page.html, , test, \n",
+        );
+        let dataset = Dataset::new(directory).unwrap();
+        let entries = dataset.entries();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].url, "https://en.wikipedia.org/wiki/A,B");
+        assert_eq!(entries[0].site.as_deref(), Some("wikipedia"));
+        assert_eq!(entries[0].split, Some(Split::Train));
+        assert_eq!(entries[0].notes.as_deref(), Some("has, comma"));
+        assert!(entries[1].filepath.ends_with("page.html"));
+        assert_eq!(entries[1].site, None);
+        assert_eq!(entries[1].split, Some(Split::Test));
+    }
+
+    #[test]
+    fn test_merge() {
+        let temp = tempfile::tempdir().unwrap();
+        let wikipedia = write_dataset(
+            temp.path(),
+            "merge-wikipedia",
+            "url, type\n\"https://en.wikipedia.org/wiki/A,B\", article\n",
+        );
+        let example = write_dataset(
+            temp.path(),
+            "merge-example",
+            "url, type, path, site\nhttps://example.com/x, special, page.html, example.com\n",
+        );
+        let merged = Dataset::merge(vec![
+            Dataset::new(&wikipedia).unwrap(),
+            Dataset::new(&example).unwrap(),
+        ]);
+        let sites = merged
+            .entries()
+            .iter()
+            .map(|entry| entry.site.as_deref().unwrap())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(sites, ["merge-wikipedia", "example.com"]);
+        assert_eq!(merged.directory(), temp.path());
+        assert_eq!(merged.entries()[1].get_content().unwrap(), "<html></html>");
+    }
+
+    #[test]
+    fn test_invalid_rows() {
+        let temp = tempfile::tempdir().unwrap();
+        let directory = write_dataset(
+            temp.path(),
+            "invalid-rows",
+            "url, type, split\n\"https://example.com/a\", \"article\", train\n\"https://example.com/b\", , test\n",
+        );
+        match Dataset::new(directory) {
+            Err(Error::Invalid { line, .. }) => assert_eq!(line, 3),
+            other => panic!("Expected a validation error, got {other:?}"),
+        }
+
+        let directory = write_dataset(
+            temp.path(),
+            "invalid-split",
+            "url, type, split\nhttps://example.com/a, article, dev\n",
+        );
+        assert!(matches!(
+            Dataset::new(directory),
+            Err(Error::Invalid { line: 2, .. })
+        ));
+    }
 }
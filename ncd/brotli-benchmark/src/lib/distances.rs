@@ -0,0 +1,132 @@
+use core::distance::{CompressedSizes, Distance};
+use core::matrix::CondensedMatrix;
+use serde::Serialize;
+
+use crate::benchmarks::get_dataset_path;
+use crate::benchmarks::metric_axioms::{MetricReport, Tolerance};
+use crate::configuration::Configuration;
+use crate::dataset::{Dataset, Entry};
+use crate::separation::Separation;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DistanceComparison {
+    pub dataset: String,
+    pub configuration: String,
+    pub distance: String,
+    pub roc_auc: f64,
+    pub overlap: f64,
+    pub triangle_checked: usize,
+    pub triangle_violations: usize,
+    pub worst_triangle: f64,
+}
+
+pub fn matrix(distance: Distance, sizes: &[Vec<CompressedSizes>]) -> Vec<Vec<f64>> {
+    sizes
+        .iter()
+        .map(|row| row.iter().map(|sizes| distance.compute(sizes)).collect())
+        .collect()
+}
+
+pub fn compare_entries(
+    dataset_name: &str,
+    entries: &[Entry],
+    configuration: &Configuration,
+) -> Vec<DistanceComparison> {
+    let urls = entries
+        .iter()
+        .map(|entry| entry.url.clone())
+        .collect::<Vec<String>>();
+    let labels = entries
+        .iter()
+        .map(|entry| entry.page_type.clone())
+        .collect::<Vec<String>>();
+    let sizes = configuration.document_sizes_matrix(&configuration.documents(entries));
+
+    Distance::all()
+        .into_iter()
+        .map(|distance| {
+            let distances =
+                CondensedMatrix::from_fn(sizes.len(), |a, b| distance.compute(&sizes[a][b]));
+            let separation =
+                Separation::new(dataset_name, &configuration.name, &labels, &distances);
+            let metric = MetricReport::new(
+                dataset_name,
+                &configuration.name,
+                &urls,
+                distance,
+                &sizes,
+                Tolerance::recommended(),
+            );
+
+            DistanceComparison {
+                dataset: dataset_name.to_string(),
+                configuration: configuration.name.clone(),
+                distance: distance.name().to_string(),
+                roc_auc: separation.roc_auc,
+                overlap: separation.overlap,
+                triangle_checked: metric.triangle.checked,
+                triangle_violations: metric.triangle.violations,
+                worst_triangle: metric
+                    .triangle
+                    .worst
+                    .first()
+                    .map_or(0.0, |worst| worst.amount),
+            }
+        })
+        .collect()
+}
+
+pub fn compare(dataset_name: &str, configuration: &Configuration) -> Vec<DistanceComparison> {
+    let dataset = Dataset::new(get_dataset_path(dataset_name)).expect("Failed to load dataset");
+    compare_entries(dataset_name, dataset.entries(), configuration)
+}
+
+pub fn print_table(comparisons: &[DistanceComparison]) {
+    println!(
+        "{:<16} {:<28} {:<8} {:>8} {:>8} {:>12} {:>10}",
+        "Dataset", "Configuration", "Distance", "AUC", "Overlap", "Triangle", "Worst"
+    );
+
+    for comparison in comparisons {
+        println!(
+            "{:<16} {:<28} {:<8} {:>8.3} {:>8.3} {:>12} {:>10.4}",
+            comparison.dataset,
+            comparison.configuration,
+            comparison.distance,
+            comparison.roc_auc,
+            comparison.overlap,
+            format!(
+                "{}/{}",
+                comparison.triangle_violations, comparison.triangle_checked
+            ),
+            comparison.worst_triangle
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic;
+
+    #[test]
+    fn test_compare_distances() {
+        let (_temp, dataset) = synthetic::temp_dataset(5, 3);
+
+        let comparisons = compare_entries(
+            "synthetic",
+            dataset.entries(),
+            &Configuration::recommended(),
+        );
+        let names = comparisons
+            .iter()
+            .map(|comparison| comparison.distance.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["ncd", "ncm_max", "ncm_sum", "cdm", "clm"]);
+
+        for comparison in &comparisons {
+            assert_eq!(comparison.triangle_checked, 12 * 11 * 10 / 6 * 3);
+            assert!(comparison.roc_auc > 0.9, "{comparison:?}");
+        }
+    }
+}
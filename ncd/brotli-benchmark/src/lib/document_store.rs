@@ -0,0 +1,164 @@
+use core::strip::StripHtml;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::dataset::Entry;
+
+const RECOMMENDED_CAPACITY: usize = 256 * 1024 * 1024;
+
+#[derive(Default)]
+struct Lru {
+    documents: HashMap<usize, (Arc<str>, u64)>,
+    recency: BTreeMap<u64, usize>,
+    size: usize,
+    tick: u64,
+}
+
+impl Lru {
+    fn touch(&mut self, index: usize) -> Option<Arc<str>> {
+        let (document, used) = self.documents.get_mut(&index)?;
+        self.recency.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.recency.insert(self.tick, index);
+        Some(document.clone())
+    }
+
+    fn insert(&mut self, index: usize, document: Arc<str>, capacity: usize) {
+        if self.touch(index).is_some() {
+            return;
+        }
+
+        self.tick += 1;
+        self.size += document.len();
+        self.documents.insert(index, (document, self.tick));
+        self.recency.insert(self.tick, index);
+
+        while self.size > capacity && self.documents.len() > 1 {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+
+            if let Some((evicted, _)) = self.documents.remove(&oldest) {
+                self.size -= evicted.len();
+            }
+        }
+    }
+}
+
+pub struct DocumentStore<'a> {
+    entries: &'a [Entry],
+    stripper: &'a (dyn StripHtml + Sync),
+    capacity: usize,
+    lru: Mutex<Lru>,
+    loads: AtomicUsize,
+}
+
+impl<'a> DocumentStore<'a> {
+    pub fn new(
+        entries: &'a [Entry],
+        stripper: &'a (dyn StripHtml + Sync),
+        capacity: usize,
+    ) -> Self {
+        Self {
+            entries,
+            stripper,
+            capacity,
+            lru: Mutex::new(Lru::default()),
+            loads: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn recommended(entries: &'a [Entry], stripper: &'a (dyn StripHtml + Sync)) -> Self {
+        Self::new(entries, stripper, RECOMMENDED_CAPACITY)
+    }
+
+    pub fn entries(&self) -> &'a [Entry] {
+        self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> io::Result<Arc<str>> {
+        if let Some(document) = self.lru.lock().unwrap().touch(index) {
+            return Ok(document);
+        }
+
+        let content = self.entries[index].get_content()?;
+        let document = Arc::<str>::from(self.stripper.strip_html(&content));
+        self.loads.fetch_add(1, Ordering::Relaxed);
+        self.lru
+            .lock()
+            .unwrap()
+            .insert(index, document.clone(), self.capacity);
+        Ok(document)
+    }
+
+    pub fn loads(&self) -> usize {
+        self.loads.load(Ordering::Relaxed)
+    }
+
+    pub fn size(&self) -> usize {
+        self.lru.lock().unwrap().size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Dataset;
+    use core::strip::filter_attributes::FilterAttributes;
+    use std::fs;
+
+    #[test]
+    fn test_lru_bound() {
+        let temp = tempfile::tempdir().unwrap();
+        let directory = temp.path().join("document-store");
+        fs::create_dir_all(directory.join("wiki")).unwrap();
+        let mut csv = "url, type\n".to_string();
+
+        for name in ["A", "B", "C"] {
+            fs::write(
+                directory.join(format!("wiki/{name}.html")),
+                format!("<html><body><p class=\"{name}\">{name}</p></body></html>"),
+            )
+            .unwrap();
+            csv += &format!("https://en.wikipedia.org/wiki/{name}, article\n");
+        }
+
+        fs::write(directory.join("dataset.csv"), csv).unwrap();
+        let dataset = Dataset::new(&directory).unwrap();
+        let stripper = FilterAttributes {};
+        let document_size = DocumentStore::recommended(dataset.entries(), &stripper)
+            .get(0)
+            .unwrap()
+            .len();
+        let store = DocumentStore::new(dataset.entries(), &stripper, 2 * document_size);
+
+        let first = store.get(0).unwrap();
+        assert_eq!(
+            first,
+            stripper
+                .strip_html(&dataset.entries()[0].get_content().unwrap())
+                .into()
+        );
+        store.get(1).unwrap();
+        store.get(0).unwrap();
+        assert_eq!(store.loads(), 2);
+
+        store.get(2).unwrap();
+        assert_eq!(store.size(), 2 * document_size);
+        store.get(0).unwrap();
+        assert_eq!(store.loads(), 3);
+        store.get(1).unwrap();
+        assert_eq!(store.loads(), 4);
+    }
+}
@@ -0,0 +1,306 @@
+use core::matrix::CondensedMatrix;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::benchmarks::get_dataset_path;
+use crate::clustering::average_linkage;
+use crate::configuration::Configuration;
+use crate::dataset::Dataset;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Evaluation {
+    pub dataset: String,
+    pub configuration: String,
+    pub clusters: usize,
+    pub adjusted_rand_index: f64,
+    pub normalized_mutual_information: f64,
+    pub purity: f64,
+    pub homogeneity: f64,
+    pub completeness: f64,
+    pub v_measure: f64,
+    pub silhouette: f64,
+}
+
+impl Evaluation {
+    pub fn new(
+        dataset: &str,
+        configuration: &str,
+        labels: &[String],
+        assignments: &[usize],
+        distances: &CondensedMatrix,
+    ) -> Self {
+        let table = Contingency::new(labels, assignments);
+        let homogeneity = table.homogeneity();
+        let completeness = table.completeness();
+
+        Self {
+            dataset: dataset.to_string(),
+            configuration: configuration.to_string(),
+            clusters: table.clusters.len(),
+            adjusted_rand_index: table.adjusted_rand_index(),
+            normalized_mutual_information: table.normalized_mutual_information(),
+            purity: table.purity(),
+            homogeneity,
+            completeness,
+            v_measure: harmonic_mean(homogeneity, completeness),
+            silhouette: silhouette(assignments, distances),
+        }
+    }
+}
+
+struct Contingency {
+    counts: BTreeMap<(usize, usize), usize>,
+    classes: Vec<usize>,
+    clusters: Vec<usize>,
+    total: usize,
+}
+
+impl Contingency {
+    fn new(labels: &[String], assignments: &[usize]) -> Self {
+        assert_eq!(
+            labels.len(),
+            assignments.len(),
+            "Every label needs a cluster assignment"
+        );
+
+        let mut class_ids = BTreeMap::new();
+        let mut cluster_ids = BTreeMap::new();
+        let mut counts = BTreeMap::new();
+
+        for (label, assignment) in labels.iter().zip(assignments) {
+            let next_class = class_ids.len();
+            let class = *class_ids.entry(label).or_insert(next_class);
+            let next_cluster = cluster_ids.len();
+            let cluster = *cluster_ids.entry(assignment).or_insert(next_cluster);
+            *counts.entry((class, cluster)).or_insert(0) += 1;
+        }
+
+        let mut classes = vec![0; class_ids.len()];
+        let mut clusters = vec![0; cluster_ids.len()];
+
+        for (&(class, cluster), &count) in &counts {
+            classes[class] += count;
+            clusters[cluster] += count;
+        }
+
+        Self {
+            counts,
+            classes,
+            clusters,
+            total: labels.len(),
+        }
+    }
+
+    fn adjusted_rand_index(&self) -> f64 {
+        let index = self.counts.values().map(|&n| pairs(n)).sum::<f64>();
+        let classes = self.classes.iter().map(|&n| pairs(n)).sum::<f64>();
+        let clusters = self.clusters.iter().map(|&n| pairs(n)).sum::<f64>();
+        let expected = classes * clusters / pairs(self.total);
+        let max = (classes + clusters) / 2.0;
+
+        if max == expected {
+            return 1.0;
+        }
+
+        (index - expected) / (max - expected)
+    }
+
+    fn mutual_information(&self) -> f64 {
+        let n = self.total as f64;
+
+        self.counts
+            .iter()
+            .map(|(&(class, cluster), &count)| {
+                let p = count as f64 / n;
+                let expected = self.classes[class] as f64 * self.clusters[cluster] as f64;
+                p * (count as f64 * n / expected).ln()
+            })
+            .sum()
+    }
+
+    fn normalized_mutual_information(&self) -> f64 {
+        let classes = entropy(&self.classes, self.total);
+        let clusters = entropy(&self.clusters, self.total);
+
+        if classes == 0.0 && clusters == 0.0 {
+            return 1.0;
+        }
+
+        2.0 * self.mutual_information() / (classes + clusters)
+    }
+
+    fn purity(&self) -> f64 {
+        let mut best = vec![0; self.clusters.len()];
+
+        for (&(_, cluster), &count) in &self.counts {
+            best[cluster] = best[cluster].max(count);
+        }
+
+        best.iter().sum::<usize>() as f64 / self.total as f64
+    }
+
+    fn homogeneity(&self) -> f64 {
+        let classes = entropy(&self.classes, self.total);
+
+        if classes == 0.0 {
+            return 1.0;
+        }
+
+        self.mutual_information() / classes
+    }
+
+    fn completeness(&self) -> f64 {
+        let clusters = entropy(&self.clusters, self.total);
+
+        if clusters == 0.0 {
+            return 1.0;
+        }
+
+        self.mutual_information() / clusters
+    }
+}
+
+fn pairs(n: usize) -> f64 {
+    (n * n.saturating_sub(1)) as f64 / 2.0
+}
+
+fn entropy(sizes: &[usize], total: usize) -> f64 {
+    sizes
+        .iter()
+        .filter(|&&size| size > 0)
+        .map(|&size| {
+            let p = size as f64 / total as f64;
+            -p * p.ln()
+        })
+        .sum()
+}
+
+fn harmonic_mean(a: f64, b: f64) -> f64 {
+    if a + b == 0.0 {
+        return 0.0;
+    }
+
+    2.0 * a * b / (a + b)
+}
+
+pub fn silhouette(assignments: &[usize], distances: &CondensedMatrix) -> f64 {
+    if assignments.is_empty() {
+        return 0.0;
+    }
+
+    let scores = (0..assignments.len()).map(|i| {
+        let mut sums: BTreeMap<usize, (f64, usize)> = BTreeMap::new();
+
+        for (j, &cluster) in assignments.iter().enumerate() {
+            if i != j {
+                let sum = sums.entry(cluster).or_insert((0.0, 0));
+                sum.0 += distances.get(i, j);
+                sum.1 += 1;
+            }
+        }
+
+        let own = match sums.remove(&assignments[i]) {
+            Some((sum, count)) => sum / count as f64,
+            None => return 0.0,
+        };
+        let nearest = sums
+            .values()
+            .map(|&(sum, count)| sum / count as f64)
+            .fold(f64::INFINITY, f64::min);
+
+        if nearest.is_infinite() || own.max(nearest) == 0.0 {
+            return 0.0;
+        }
+
+        (nearest - own) / own.max(nearest)
+    });
+
+    scores.sum::<f64>() / assignments.len() as f64
+}
+
+pub fn evaluate(dataset_name: &str, configurations: &[Configuration]) -> Vec<Evaluation> {
+    let dataset = Dataset::new(get_dataset_path(dataset_name)).expect("Failed to load dataset");
+    let entries = dataset.entries();
+    let labels = entries
+        .iter()
+        .map(|entry| entry.page_type.clone())
+        .collect::<Vec<String>>();
+    let types = labels.iter().collect::<BTreeSet<_>>().len();
+
+    configurations
+        .iter()
+        .map(|configuration| {
+            let distances =
+                configuration.document_distance_matrix(&configuration.documents(entries));
+            let assignments = average_linkage(&distances, types);
+            Evaluation::new(
+                dataset_name,
+                &configuration.name,
+                &labels,
+                &assignments,
+                &distances,
+            )
+        })
+        .collect()
+}
+
+pub fn print_table(evaluations: &[Evaluation]) {
+    println!(
+        "{:<16} {:<28} {:>8} {:>7} {:>7} {:>7} {:>7} {:>10}",
+        "Dataset", "Configuration", "Clusters", "ARI", "NMI", "Purity", "V", "Silhouette"
+    );
+
+    for evaluation in evaluations {
+        println!(
+            "{:<16} {:<28} {:>8} {:>7.3} {:>7.3} {:>7.3} {:>7.3} {:>10.3}",
+            evaluation.dataset,
+            evaluation.configuration,
+            evaluation.clusters,
+            evaluation.adjusted_rand_index,
+            evaluation.normalized_mutual_information,
+            evaluation.purity,
+            evaluation.v_measure,
+            evaluation.silhouette
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_scores() {
+        let labels = labels(&["movie", "movie", "video", "video", "video", "user_list"]);
+        let distances = CondensedMatrix::new(6);
+
+        let perfect = Evaluation::new("test", "test", &labels, &[2, 2, 0, 0, 0, 1], &distances);
+        assert_approx_eq!(perfect.adjusted_rand_index, 1.0);
+        assert_approx_eq!(perfect.normalized_mutual_information, 1.0);
+        assert_approx_eq!(perfect.purity, 1.0);
+        assert_approx_eq!(perfect.v_measure, 1.0);
+
+        let merged = Evaluation::new("test", "test", &labels, &[0, 0, 0, 0, 0, 1], &distances);
+        assert_approx_eq!(merged.adjusted_rand_index, 4.0 / 13.0, 1e-6);
+        assert_approx_eq!(merged.purity, 4.0 / 6.0, 1e-6);
+        assert_approx_eq!(merged.completeness, 1.0, 1e-6);
+        assert!(merged.homogeneity < 1.0);
+    }
+
+    #[test]
+    fn test_silhouette() {
+        let distances = CondensedMatrix::from_square(&[
+            vec![0.0, 0.1, 0.9, 0.9],
+            vec![0.1, 0.0, 0.9, 0.9],
+            vec![0.9, 0.9, 0.0, 0.1],
+            vec![0.9, 0.9, 0.1, 0.0],
+        ]);
+        assert_approx_eq!(silhouette(&[0, 0, 1, 1], &distances), 8.0 / 9.0, 1e-6);
+        assert!(silhouette(&[0, 1, 0, 1], &distances) < 0.0);
+    }
+}
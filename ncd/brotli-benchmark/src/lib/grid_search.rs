@@ -0,0 +1,321 @@
+use core::alphabet::Encoding;
+use core::compress::lz::Variant;
+use itertools::iproduct;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::benchmarks::get_dataset_path;
+use crate::configuration::{Backend, Configuration, Stripper};
+use crate::dataset::Dataset;
+use crate::separation::Separation;
+use crate::{BenchmarkResult, Score};
+
+// Separation (ROC AUC), self-distance and runtime have different units, so the score is a
+// weighted sum: separation counts positively, self-distance and log10(1 + milliseconds)
+// negatively.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Weights {
+    pub separation: f64,
+    pub self_distance: f64,
+    pub runtime: f64,
+}
+
+impl Weights {
+    pub fn new(separation: f64, self_distance: f64, runtime: f64) -> Self {
+        Self {
+            separation,
+            self_distance,
+            runtime,
+        }
+    }
+
+    // A self-distance of 0.01 costs as much as 0.01 of ROC AUC, and every tenfold increase in
+    // runtime costs 0.05 of ROC AUC.
+    pub fn recommended() -> Self {
+        Self::new(1.0, 1.0, 0.05)
+    }
+
+    pub fn score(&self, separation: f64, self_distance: f64, duration: Duration) -> Score {
+        let separation = self.separation * separation;
+        let self_distance = -self.self_distance * self_distance;
+        let runtime = -self.runtime * (1.0 + duration.as_secs_f64() * 1000.0).log10();
+
+        Score {
+            separation,
+            self_distance,
+            runtime,
+            total: separation + self_distance + runtime,
+        }
+    }
+}
+
+pub struct GridSearch {
+    pub strippers: Vec<Stripper>,
+    pub backends: Vec<Backend>,
+    // Tokenized variants are searched in addition to plain bytes, for these encodings.
+    pub encodings: Vec<Encoding>,
+    pub weights: Weights,
+}
+
+impl GridSearch {
+    pub fn new(strippers: Vec<Stripper>, backends: Vec<Backend>) -> Self {
+        Self {
+            strippers,
+            backends,
+            encodings: Vec::new(),
+            weights: Weights::recommended(),
+        }
+    }
+
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_encodings(mut self, encodings: Vec<Encoding>) -> Self {
+        self.encodings = encodings;
+        self
+    }
+
+    pub fn recommended() -> Self {
+        let mut backends = Self::brotli(3..11, 20..=22);
+        backends.extend(Self::lz());
+        backends.extend(Self::ppm(2..=6));
+        Self::new(Stripper::all(), backends)
+    }
+
+    pub fn lz() -> Vec<Backend> {
+        [Variant::Lz76, Variant::Lz78]
+            .into_iter()
+            .map(|variant| Backend::Lz { variant })
+            .collect()
+    }
+
+    pub fn ppm(orders: impl IntoIterator<Item = usize>) -> Vec<Backend> {
+        orders
+            .into_iter()
+            .map(|order| Backend::Ppm { order })
+            .collect()
+    }
+
+    pub fn brotli(
+        qualities: impl IntoIterator<Item = u32>,
+        lg_window_sizes: impl IntoIterator<Item = u32, IntoIter: Clone>,
+    ) -> Vec<Backend> {
+        iproduct!(qualities, lg_window_sizes)
+            .map(|(quality, lg_window_size)| Backend::Brotli {
+                quality,
+                lg_window_size,
+            })
+            .collect()
+    }
+
+    pub fn configurations(&self) -> Vec<(Backend, Configuration)> {
+        let encodings = [None]
+            .into_iter()
+            .chain(self.encodings.iter().copied().map(Some))
+            .collect::<Vec<Option<Encoding>>>();
+
+        iproduct!(&self.strippers, &self.backends, &encodings)
+            .map(|(&stripper, &backend, &encoding)| {
+                let configuration = Configuration::from_parts(stripper, backend);
+                let configuration = match encoding {
+                    Some(encoding) => configuration.with_encoding(encoding),
+                    None => configuration,
+                };
+                (backend, configuration)
+            })
+            .collect()
+    }
+
+    pub fn run(&self, dataset_names: &[&str]) -> Vec<BenchmarkResult> {
+        let corpora = dataset_names
+            .iter()
+            .map(|&name| {
+                let dataset = Dataset::new(get_dataset_path(name)).expect("Failed to load dataset");
+                let pages = dataset
+                    .entries()
+                    .iter()
+                    .map(|entry| entry.get_content().unwrap())
+                    .collect::<Vec<String>>();
+                let labels = dataset
+                    .entries()
+                    .iter()
+                    .map(|entry| entry.page_type.clone())
+                    .collect::<Vec<String>>();
+                (name, pages, labels)
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = self
+            .configurations()
+            .iter()
+            .map(|(backend, configuration)| {
+                let mut self_distances = Vec::new();
+                let mut separations = Vec::new();
+                let mut duration = Duration::ZERO;
+
+                for (name, pages, labels) in &corpora {
+                    let start = Instant::now();
+                    let inputs = configuration.page_inputs(pages);
+                    let distances = configuration.distance_matrix(&inputs);
+                    self_distances.extend(configuration.self_distances(&inputs));
+                    duration += start.elapsed();
+
+                    separations.push(
+                        Separation::new(name, &configuration.name, labels, &distances).roc_auc,
+                    );
+                }
+
+                let self_distance = mean(&self_distances);
+                let separation = mean(&separations);
+                let result = BenchmarkResult {
+                    configuration: configuration.name.clone(),
+                    family: backend.family().to_string(),
+                    self_distance,
+                    separation,
+                    duration,
+                    score: self.weights.score(separation, self_distance, duration),
+                };
+
+                println!(
+                    "{}: self-distance {:.6}, separation {:.3}, time {:?}",
+                    result.configuration, result.self_distance, result.separation, result.duration
+                );
+
+                result
+            })
+            .collect::<Vec<BenchmarkResult>>();
+
+        rank(&mut results);
+        results
+    }
+}
+
+pub fn rank(results: &mut [BenchmarkResult]) {
+    results.sort_by(|a, b| {
+        b.score
+            .total
+            .total_cmp(&a.score.total)
+            .then(a.duration.cmp(&b.duration))
+    });
+}
+
+pub fn print_table(results: &[BenchmarkResult]) {
+    println!("Score = separation + self-distance + runtime terms, each weighted");
+    println!(
+        "{:>4} {:<44} {:>8} {:>10} {:>13} {:>8} {:>12}",
+        "Rank", "Configuration", "Score", "Separation", "Self-distance", "Runtime", "Time"
+    );
+
+    for (rank, result) in results.iter().enumerate() {
+        println!(
+            "{:>4} {:<44} {:>8.3} {:>10.3} {:>13.6} {:>8.3} {:>12}",
+            rank + 1,
+            result.configuration,
+            result.score.total,
+            result.score.separation,
+            result.score.self_distance,
+            result.score.runtime,
+            format!("{:.3?}", result.duration)
+        );
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    let values = values
+        .iter()
+        .filter(|value| !value.is_nan())
+        .collect::<Vec<&f64>>();
+
+    if values.is_empty() {
+        return f64::NAN;
+    }
+
+    values.iter().copied().sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        configuration: &str,
+        self_distance: f64,
+        separation: f64,
+        millis: u64,
+    ) -> BenchmarkResult {
+        BenchmarkResult {
+            configuration: configuration.to_string(),
+            family: "brotli".to_string(),
+            self_distance,
+            separation,
+            duration: Duration::from_millis(millis),
+            score: Weights::recommended().score(
+                separation,
+                self_distance,
+                Duration::from_millis(millis),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut results = vec![
+            result("slow", 0.01, 0.9, 200),
+            result("poor", 0.01, 0.6, 10),
+            result("fast", 0.01, 0.9, 100),
+            result("noisy", 0.2, 0.9, 10),
+            result("glacial", 0.01, 0.92, 100_000),
+        ];
+        rank(&mut results);
+
+        let order = results
+            .iter()
+            .map(|result| result.configuration.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(order, ["fast", "slow", "glacial", "noisy", "poor"]);
+
+        let score = results[0].score;
+        assert_eq!(score.separation, 0.9);
+        assert_eq!(score.self_distance, -0.01);
+        assert!((score.runtime + 0.05 * 101f64.log10()).abs() < 1e-12);
+        assert_eq!(
+            score.total,
+            score.separation + score.self_distance + score.runtime
+        );
+
+        let ignore_runtime = Weights::new(1.0, 1.0, 0.0);
+        let glacial = &results[2];
+        assert_eq!(
+            ignore_runtime
+                .score(glacial.separation, glacial.self_distance, glacial.duration)
+                .total,
+            0.92 - 0.01
+        );
+    }
+
+    #[test]
+    fn test_configurations() {
+        let search = GridSearch::new(Stripper::all(), GridSearch::brotli(4..6, 20..=22));
+        assert_eq!(search.configurations().len(), 12);
+
+        let search = GridSearch::new(Stripper::all(), GridSearch::lz());
+        let names = search
+            .configurations()
+            .into_iter()
+            .map(|(_, configuration)| configuration.name)
+            .collect::<Vec<String>>();
+        assert!(names.iter().any(|name| name.ends_with("lz76")), "{names:?}");
+        assert!(names.iter().any(|name| name.ends_with("lz78")), "{names:?}");
+
+        let search = GridSearch::new(vec![Stripper::StripContent], GridSearch::ppm(2..=4));
+        assert_eq!(search.configurations()[0].0.family(), "ppm");
+        assert_eq!(search.configurations().len(), 3);
+
+        let search = GridSearch::new(Stripper::all(), GridSearch::brotli(4..6, 20..=22))
+            .with_encodings(vec![Encoding::Varint]);
+        assert_eq!(search.configurations().len(), 24);
+        assert!(search.configurations()[1].1.name.ends_with("/varint"));
+    }
+}
@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fs, io};
+
+use crate::archive::Format;
+use crate::dataset::{Dataset, Error};
+use crate::url_path::url_to_path;
+
+pub const FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub url: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+impl ManifestEntry {
+    pub fn new(url: &str, path: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            path: path.to_string(),
+            size: None,
+            sha256: None,
+            fetched_at: None,
+            status: None,
+        }
+    }
+
+    pub fn with_content(mut self, content: &[u8]) -> Self {
+        self.size = Some(content.len() as u64);
+        self.sha256 = Some(sha256_hex(content));
+        self
+    }
+}
+
+pub fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn relative_path(directory: &Path, filepath: &Path, url: &str) -> String {
+    filepath
+        .strip_prefix(directory)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| url_to_path(url))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn from_dataset(dataset: &Dataset) -> Self {
+        let mut rows = dataset
+            .entries()
+            .iter()
+            .map(|entry| {
+                let path = relative_path(dataset.directory(), &entry.filepath, &entry.url);
+                let mut recorded = ManifestEntry::new(&entry.url, &path);
+
+                if let Ok(content) = entry.get_bytes() {
+                    recorded = recorded.with_content(&content);
+                }
+
+                if entry.sha256.is_none() || entry.sha256 == recorded.sha256 {
+                    recorded.fetched_at = entry.fetched_at.clone();
+                }
+
+                (entry.line, recorded)
+            })
+            .chain(dataset.missing().iter().map(|missing| {
+                let path = relative_path(dataset.directory(), &missing.filepath, &missing.url);
+                (missing.line, ManifestEntry::new(&missing.url, &path))
+            }))
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|(line, _)| *line);
+
+        Self {
+            entries: rows.into_iter().map(|(_, entry)| entry).collect(),
+        }
+    }
+
+    pub fn merge_metadata(&mut self, previous: &Manifest) {
+        let previous = previous.by_url();
+
+        for entry in &mut self.entries {
+            if let Some(recorded) = previous.get(entry.url.as_str()) {
+                if entry.sha256.is_none() || entry.sha256 == recorded.sha256 {
+                    entry.fetched_at = entry.fetched_at.take().or(recorded.fetched_at.clone());
+                    entry.status = entry.status.or(recorded.status);
+                }
+            }
+        }
+    }
+
+    pub fn changed<'a>(&'a self, previous: &Manifest) -> Vec<&'a ManifestEntry> {
+        let previous = previous.by_url();
+
+        self.entries
+            .iter()
+            .filter(|entry| {
+                previous.get(entry.url.as_str()).is_some_and(|recorded| {
+                    recorded.sha256.is_some()
+                        && entry.sha256.is_some()
+                        && recorded.sha256 != entry.sha256
+                })
+            })
+            .collect()
+    }
+
+    pub fn get(&self, url: &str) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|entry| entry.url == url)
+    }
+
+    pub fn upsert(&mut self, entry: ManifestEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|recorded| recorded.url == entry.url)
+        {
+            Some(recorded) => *recorded = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    fn by_url(&self) -> HashMap<&str, &ManifestEntry> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.url.as_str(), entry))
+            .collect()
+    }
+
+    pub fn load<P: AsRef<Path>>(directory: P) -> Result<Option<Self>, Error> {
+        let path = directory.as_ref().join(FILE_NAME);
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        Self::parse(&fs::read_to_string(path)?).map(Some)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        serde_json::from_str(text).map_err(|error| Error::Invalid {
+            line: error.line(),
+            message: format!("{FILE_NAME}: {error}"),
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, directory: P) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(directory.as_ref().join(FILE_NAME), json + "\n")?;
+        Ok(())
+    }
+}
+
+// Rewrites the manifest of a dataset, keeping the fetch metadata of unchanged pages, and
+// returns the URLs whose content differs from the previous manifest. Archives are read-only,
+// so they are rejected before any page is hashed.
+pub fn update<P: AsRef<Path>>(directory: P) -> Result<Vec<String>, Error> {
+    let directory = directory.as_ref();
+
+    if directory.is_file() && Format::detect(directory).is_some() {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Cannot write a manifest into an archive, extract it first",
+        )));
+    }
+
+    let dataset = Dataset::new(directory)?;
+    let previous = Manifest::load(directory)?.unwrap_or_default();
+    let mut manifest = Manifest::from_dataset(&dataset);
+    manifest.merge_metadata(&previous);
+    let changed = manifest
+        .changed(&previous)
+        .into_iter()
+        .map(|entry| entry.url.clone())
+        .collect();
+    manifest.save(directory)?;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthetic;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let directory = temp.path().join("manifest");
+        fs::create_dir_all(directory.join("title/tt0111161")).unwrap();
+        fs::write(
+            directory.join("title/tt0111161/?ref_=chttp_t_1.html"),
+            "<html></html>",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("dataset.csv"),
+            "url, type\n\
+             \"https://www.imdb.com/chart/top/\", \"user_list\"\n\
+             \"https://www.imdb.com/title/tt0111161/?ref_=chttp_t_1\", \"movie\"\n",
+        )
+        .unwrap();
+
+        let dataset = Dataset::new(&directory).unwrap();
+        let manifest = Manifest::from_dataset(&dataset);
+        let paths = manifest
+            .entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            paths,
+            [
+                "chart/top/index.html",
+                "title/tt0111161/?ref_=chttp_t_1.html"
+            ]
+        );
+
+        assert_eq!(manifest.entries[0].sha256, None);
+        assert_eq!(manifest.entries[1].size, Some(13));
+        assert_eq!(
+            manifest.entries[1].sha256.as_deref(),
+            Some("b633a587c652d02386c4f16f8c6f6aab7352d97f16367c3c40576214372dd628")
+        );
+
+        let mut previous = manifest.clone();
+        previous.entries[1].status = Some(200);
+        previous.entries[1].fetched_at = Some("2025-08-16T10:00:00Z".to_string());
+        previous.save(&directory).unwrap();
+
+        let dataset = Dataset::new(&directory).unwrap();
+        assert_eq!(dataset.entries()[0].sha256, manifest.entries[1].sha256);
+        assert_eq!(
+            dataset.entries()[0].fetched_at.as_deref(),
+            Some("2025-08-16T10:00:00Z")
+        );
+
+        let mut current = Manifest::from_dataset(&dataset);
+        current.merge_metadata(&previous);
+        assert_eq!(current, previous);
+        assert!(current.changed(&previous).is_empty());
+
+        fs::write(
+            directory.join("title/tt0111161/?ref_=chttp_t_1.html"),
+            "<html><body></body></html>",
+        )
+        .unwrap();
+        let mut refetched = Manifest::from_dataset(&Dataset::new(&directory).unwrap());
+        refetched.merge_metadata(&previous);
+        assert_eq!(refetched.changed(&previous).len(), 1);
+        assert_eq!(refetched.entries[1].status, None);
+        assert_eq!(refetched.entries[1].fetched_at, None);
+
+        assert_eq!(Manifest::load(&directory).unwrap(), Some(previous));
+    }
+
+    #[test]
+    fn test_update() {
+        let (temp, dataset) = synthetic::temp_dataset(2, 2);
+        let directory = dataset.directory();
+        assert!(update(directory).unwrap().is_empty());
+
+        let mut recorded = Manifest::load(directory).unwrap().unwrap();
+        for entry in &mut recorded.entries {
+            entry.status = Some(200);
+        }
+        recorded.save(directory).unwrap();
+
+        let changed = &dataset.entries()[3];
+        let html = fs::read_to_string(&changed.filepath).unwrap();
+        fs::write(&changed.filepath, html.replace("</body>", "<hr></body>")).unwrap();
+
+        assert_eq!(update(directory).unwrap(), [changed.url.as_str()]);
+        let manifest = Manifest::load(directory).unwrap().unwrap();
+        let statuses = manifest
+            .entries
+            .iter()
+            .filter(|entry| entry.status.is_some())
+            .count();
+        assert_eq!(statuses, manifest.entries.len() - 1);
+
+        let archive = temp.path().join("manifest.zip");
+        fs::write(&archive, []).unwrap();
+        let error = update(&archive).unwrap_err();
+        assert!(error.to_string().contains("extract it first"));
+        assert!(!temp.path().join(FILE_NAME).exists());
+    }
+}
@@ -0,0 +1,307 @@
+use core::matrix::CondensedMatrix;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Update {
+    pub kept: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub computed: usize,
+}
+
+const MAGIC: &[u8; 4] = b"NCDS";
+const VERSION: u64 = 1;
+
+// A distance matrix whose rows are identified by keys, so it can be carried over between
+// runs and only the rows of changed documents recomputed. The file records the name of the
+// configuration that computed the distances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixStore {
+    pub configuration: String,
+    pub keys: Vec<String>,
+    pub matrix: CondensedMatrix,
+}
+
+impl MatrixStore {
+    pub fn new(configuration: &str) -> Self {
+        Self {
+            configuration: configuration.to_string(),
+            keys: Vec::new(),
+            matrix: CondensedMatrix::new(0),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(invalid_data("Not a distance matrix store".to_string()));
+        }
+
+        let version = read_u64(&mut reader)?;
+
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "Unsupported matrix store version {version}"
+            )));
+        }
+
+        let configuration = read_string(&mut reader)?;
+        let count = read_u64(&mut reader)?;
+        let keys = (0..count)
+            .map(|_| read_string(&mut reader))
+            .collect::<io::Result<Vec<String>>>()?;
+        let matrix = CondensedMatrix::read(reader)?;
+
+        if matrix.size() != keys.len() {
+            return Err(invalid_data(format!(
+                "{} keys for a matrix of {}",
+                keys.len(),
+                matrix.size()
+            )));
+        }
+
+        Ok(Self {
+            configuration,
+            keys,
+            matrix,
+        })
+    }
+
+    // Starts over when there is no store yet, when it cannot be read, or when it was computed
+    // with another configuration, since its distances are only a cache.
+    pub fn load_or_new(path: &Path, configuration: &str) -> io::Result<Self> {
+        match Self::load(path) {
+            Ok(store) if store.configuration == configuration => Ok(store),
+            Ok(_) => Ok(Self::new(configuration)),
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::NotFound
+                        | io::ErrorKind::InvalidData
+                        | io::ErrorKind::UnexpectedEof
+                ) =>
+            {
+                Ok(Self::new(configuration))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Writes next to `path` and renames, so an interrupted save leaves the old store intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".part");
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        write_string(&mut writer, &self.configuration)?;
+        writer.write_all(&(self.keys.len() as u64).to_le_bytes())?;
+
+        for key in &self.keys {
+            write_string(&mut writer, key)?;
+        }
+
+        self.matrix.write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&partial, path)
+    }
+
+    // Brings the matrix in line with `keys`: rows of missing keys are dropped, new keys get
+    // a row, and the result is ordered like `keys`. `distance` takes indices into `keys`.
+    pub fn update(
+        &mut self,
+        keys: &[String],
+        distance: impl Fn(usize, usize) -> f64 + Sync,
+    ) -> Update {
+        let positions = keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (key.as_str(), index))
+            .collect::<HashMap<&str, usize>>();
+        let mut seen = HashSet::new();
+        let kept = (0..self.keys.len())
+            .filter(|&index| {
+                positions
+                    .get(self.keys[index].as_str())
+                    .is_some_and(|&position| seen.insert(position))
+            })
+            .collect::<Vec<usize>>();
+        let removed = self.keys.len() - kept.len();
+
+        self.matrix = self.matrix.select(&kept);
+        self.keys = kept.iter().map(|&index| self.keys[index].clone()).collect();
+
+        let mut rows = self
+            .keys
+            .iter()
+            .map(|key| positions[key.as_str()])
+            .collect::<Vec<usize>>();
+        let mut computed = 0;
+
+        for position in 0..keys.len() {
+            if !seen.insert(position) {
+                continue;
+            }
+
+            computed += rows.len();
+            self.matrix.push(|row| distance(rows[row], position));
+            rows.push(position);
+        }
+
+        let mut order = vec![0; keys.len()];
+        for (row, &position) in rows.iter().enumerate() {
+            order[position] = row;
+        }
+
+        self.matrix = self.matrix.select(&order);
+        self.keys = keys.to_vec();
+
+        Update {
+            kept: kept.len(),
+            added: keys.len() - kept.len(),
+            removed,
+            computed,
+        }
+    }
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut word = [0; 8];
+    reader.read_exact(&mut word)?;
+    Ok(u64::from_le_bytes(word))
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let length = read_u64(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 != length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    String::from_utf8(bytes).map_err(|error| invalid_data(error.to_string()))
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    writer.write_all(&(value.len() as u64).to_le_bytes())?;
+    writer.write_all(value.as_bytes())
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::Configuration;
+    use crate::dataset::Dataset;
+    use crate::synthetic;
+    use std::sync::Mutex;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_update_and_reload() {
+        let symmetric = |a: &str, b: &str| (a.len() * b.len()) as f64;
+        let first = keys(&["a", "bb", "ccc"]);
+        let mut store = MatrixStore::new("test");
+
+        let update = store.update(&first, |a, b| symmetric(&first[a], &first[b]));
+        assert_eq!((update.added, update.computed), (3, 3));
+
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("store").join("matrix.bin");
+        store.save(&path).unwrap();
+        let mut store = MatrixStore::load_or_new(&path, "test").unwrap();
+        assert_eq!(store.keys, first);
+        assert!(!path.with_extension("bin.part").exists());
+        assert!(MatrixStore::load_or_new(&path, "other")
+            .unwrap()
+            .keys
+            .is_empty());
+
+        let truncated = temp.path().join("store").join("truncated.bin");
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&truncated, &bytes[..bytes.len() - 4]).unwrap();
+        assert!(MatrixStore::load(&truncated).is_err());
+        assert!(MatrixStore::load_or_new(&truncated, "test")
+            .unwrap()
+            .keys
+            .is_empty());
+
+        let second = keys(&["dddd", "ccc", "a"]);
+        let calls = Mutex::new(Vec::new());
+        let update = store.update(&second, |a, b| {
+            calls.lock().unwrap().push((a, b));
+            symmetric(&second[a], &second[b])
+        });
+        assert_eq!(
+            update,
+            Update {
+                kept: 2,
+                added: 1,
+                removed: 1,
+                computed: 2
+            }
+        );
+        assert!(calls.into_inner().unwrap().iter().all(|&(_, b)| b == 0));
+        assert_eq!(store.keys, second);
+
+        for a in 0..second.len() {
+            for b in 0..second.len() {
+                if a != b {
+                    assert_eq!(store.matrix.get(a, b), symmetric(&second[a], &second[b]));
+                }
+            }
+        }
+
+        assert!(
+            MatrixStore::load_or_new(&path.with_extension("missing"), "test")
+                .unwrap()
+                .keys
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_incremental_distance_matrix() {
+        let (_temp, dataset) = synthetic::temp_dataset(3, 2);
+        let path = dataset.directory().join("matrix.bin");
+        let configuration = Configuration::recommended();
+
+        let documents = configuration.documents(dataset.entries());
+        let (_, update) = configuration
+            .incremental_distance_matrix(&documents, &path)
+            .unwrap();
+        assert_eq!(update.computed, 8 * 7 / 2);
+
+        let changed = &dataset.entries()[5].filepath;
+        let html = fs::read_to_string(changed).unwrap();
+        fs::write(changed, html.replace("</body>", "<footer></footer></body>")).unwrap();
+
+        let dataset = Dataset::new(dataset.directory()).unwrap();
+        let documents = configuration.documents(dataset.entries());
+        let (matrix, update) = configuration
+            .incremental_distance_matrix(&documents, &path)
+            .unwrap();
+        assert_eq!((update.kept, update.added, update.removed), (7, 1, 1));
+        assert_eq!(update.computed, 7);
+        assert_eq!(matrix, configuration.document_distance_matrix(&documents));
+    }
+}
@@ -1,18 +1,57 @@
+pub mod archive;
 pub mod benchmarks;
+pub mod clustering;
+pub mod configuration;
+pub mod csv;
 pub mod dataset;
+pub mod distances;
+pub mod document_store;
+pub mod evaluation;
+pub mod grid_search;
+pub mod manifest;
+pub mod matrix_store;
+pub mod page_types;
+pub mod separation;
+pub mod splits;
+pub mod synthetic;
+pub mod url_path;
+pub mod validation;
 
 extern crate plotters;
 
 use plotters::evcxr::SVGWrapper;
 use plotters::prelude::*;
+use serde::Serialize;
+use std::path::Path;
 use std::time::Duration;
+use std::{fs, io};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BenchmarkResult {
-    pub quality: u32,
-    pub lg_window_size: u32,
-    pub compression_ratio: f64,
+    pub configuration: String,
+    pub family: String,
+    pub self_distance: f64,
+    pub separation: f64,
     pub duration: Duration,
+    pub score: Score,
+}
+
+// The weighted terms of a grid search score, see `grid_search::Weights`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Score {
+    pub separation: f64,
+    pub self_distance: f64,
+    pub runtime: f64,
+    pub total: f64,
+}
+
+pub fn save_json<T: Serialize + ?Sized, P: AsRef<Path>>(value: &T, path: P) -> io::Result<()> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let json = serde_json::to_string_pretty(value)?;
+    fs::write(path, json)
 }
 
 #[allow(dead_code)]
@@ -21,7 +60,7 @@ pub fn point_series(results: &[BenchmarkResult]) -> SVGWrapper {
         root.fill(&WHITE)?;
         let mut chart = ChartBuilder::on(&root)
             .caption(
-                "NCD Brotli: distance and compression time",
+                "NCD grid search: self-distance and compression time",
                 ("sans-serif", 40),
             )
             .margin(10)
@@ -42,48 +81,38 @@ pub fn point_series(results: &[BenchmarkResult]) -> SVGWrapper {
                     .log_scale(),
                 (results
                     .iter()
-                    .map(|r| r.compression_ratio)
+                    .map(|r| r.self_distance)
                     .filter(|&x| x > 0.0)
                     .fold(f64::INFINITY, f64::min)
                     * 0.9
-                    ..results
-                        .iter()
-                        .map(|r| r.compression_ratio)
-                        .fold(0.0, f64::max)
-                        * 1.1)
+                    ..results.iter().map(|r| r.self_distance).fold(0.0, f64::max) * 1.1)
                     .log_scale(),
             )?;
 
         chart
             .configure_mesh()
             .x_desc("Compression Time (seconds). The lower, the better")
-            .y_desc("Distance. The lower, the better")
+            .y_desc("Self-distance. The lower, the better")
             .draw()?;
 
         for result in results {
-            let color = match result.quality {
-                0..=2 => &RED,
-                3..=5 => &BLUE,
-                6..=8 => &GREEN,
+            let color = match result.family.as_str() {
+                "brotli" => &BLUE,
                 _ => &MAGENTA,
             };
 
             chart
                 .draw_series(PointSeries::of_element(
-                    vec![(result.duration.as_secs_f64(), result.compression_ratio)],
+                    vec![(result.duration.as_secs_f64(), result.self_distance)],
                     5,
                     color,
                     &|c, s, st| {
                         EmptyElement::at(c)
                             + Circle::new((0, 0), s, st.filled())
-                            + Text::new(
-                                format!("{}, {}", result.lg_window_size, result.quality),
-                                (0, 10),
-                                ("sans-serif", 10),
-                            )
+                            + Text::new(result.configuration.clone(), (0, 10), ("sans-serif", 10))
                     },
                 ))?
-                .label(format!("Q{}", result.quality))
+                .label(result.family.clone())
                 .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
         }
 
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::csv;
+use crate::dataset::Error;
+
+pub const FILE_NAME: &str = "page_types.csv";
+const DEFAULT_ICON: &str = "▫️";
+const DEFAULT_COLOR: &str = "#7f7f7f";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageType {
+    pub name: String,
+    pub icon: String,
+    pub label: String,
+    pub color: String,
+}
+
+impl PageType {
+    pub fn unknown(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            icon: DEFAULT_ICON.to_string(),
+            label: name.to_string(),
+            color: DEFAULT_COLOR.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    types: BTreeMap<String, PageType>,
+}
+
+impl Registry {
+    pub fn for_dataset<P: AsRef<Path>>(directory: P) -> Result<Self, Error> {
+        let directory = directory.as_ref();
+        let mut registry = Self::default();
+
+        if let Some(parent) = directory.parent() {
+            registry.load_if_exists(parent.join(FILE_NAME))?;
+        }
+
+        registry.load_if_exists(directory.join(FILE_NAME))?;
+        Ok(registry)
+    }
+
+    fn load_if_exists<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        if path.as_ref().is_file() {
+            self.load(path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        self.load_str(&fs::read_to_string(path)?)
+    }
+
+    pub fn load_str(&mut self, text: &str) -> Result<(), Error> {
+        let mut records = csv::parse(text)?.into_iter();
+
+        let header = match records.next() {
+            Some(header) => header
+                .fields
+                .iter()
+                .map(|name| name.trim().to_lowercase())
+                .collect::<Vec<String>>(),
+            None => return Ok(()),
+        };
+
+        for record in records {
+            let field = |name: &str| {
+                header
+                    .iter()
+                    .position(|column| column == name)
+                    .and_then(|index| record.fields.get(index))
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+            };
+
+            let name = field("type").ok_or_else(|| Error::Invalid {
+                line: record.line,
+                message: "Missing value for column 'type'".to_string(),
+            })?;
+            let key = name.to_lowercase();
+            let default = self
+                .types
+                .remove(&key)
+                .unwrap_or_else(|| PageType::unknown(&key));
+
+            self.types.insert(
+                key.clone(),
+                PageType {
+                    name: key,
+                    icon: field("icon").map_or(default.icon, str::to_string),
+                    label: field("label").map_or(default.label, str::to_string),
+                    color: field("color").map_or(default.color, str::to_string),
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &Registry) {
+        for (name, page_type) in &other.types {
+            self.types
+                .entry(name.clone())
+                .or_insert_with(|| page_type.clone());
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.contains_key(&name.trim().to_lowercase())
+    }
+
+    pub fn get(&self, name: &str) -> PageType {
+        let key = name.trim().to_lowercase();
+        self.types
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| PageType::unknown(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset_overrides_global() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("datasets");
+        let directory = root.join("site");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            root.join(FILE_NAME),
+            "type, icon, label, color\narticle, 📄, Article, #1f77b4\nvideo, 📹, Video, #aec7e8\n",
+        )
+        .unwrap();
+        fs::write(
+            directory.join(FILE_NAME),
+            "type, icon\nVideo, 🎞️\nforum, 💬\n",
+        )
+        .unwrap();
+
+        let registry = Registry::for_dataset(&directory).unwrap();
+
+        assert_eq!(registry.get("article").label, "Article");
+        assert_eq!(registry.get("video").icon, "🎞️");
+        assert_eq!(registry.get("video").color, "#aec7e8");
+        assert_eq!(registry.get("forum").color, DEFAULT_COLOR);
+        assert!(registry.contains("Forum"));
+        assert!(!registry.contains("gallery"));
+        assert_eq!(registry.get("gallery"), PageType::unknown("gallery"));
+    }
+}
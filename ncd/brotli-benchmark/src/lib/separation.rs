@@ -0,0 +1,228 @@
+use core::matrix::CondensedMatrix;
+use serde::Serialize;
+
+use crate::benchmarks::get_dataset_path;
+use crate::configuration::Configuration;
+use crate::dataset::Dataset;
+
+const HISTOGRAM_BINS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub q25: f64,
+    pub median: f64,
+    pub q75: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn new(values: &[f64]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let mean = if sorted.is_empty() {
+            0.0
+        } else {
+            sorted.iter().sum::<f64>() / sorted.len() as f64
+        };
+
+        Self {
+            count: sorted.len(),
+            mean,
+            min: quantile(&sorted, 0.0),
+            q25: quantile(&sorted, 0.25),
+            median: quantile(&sorted, 0.5),
+            q75: quantile(&sorted, 0.75),
+            max: quantile(&sorted, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Separation {
+    pub dataset: String,
+    pub configuration: String,
+    pub intra: Summary,
+    pub inter: Summary,
+    pub overlap: f64,
+    pub roc_auc: f64,
+}
+
+impl Separation {
+    pub fn new(
+        dataset: &str,
+        configuration: &str,
+        labels: &[String],
+        distances: &CondensedMatrix,
+    ) -> Self {
+        let mut intra = Vec::new();
+        let mut inter = Vec::new();
+
+        for a in 0..labels.len() {
+            for b in a + 1..labels.len() {
+                if labels[a] == labels[b] {
+                    intra.push(distances.get(a, b));
+                } else {
+                    inter.push(distances.get(a, b));
+                }
+            }
+        }
+
+        Self {
+            dataset: dataset.to_string(),
+            configuration: configuration.to_string(),
+            intra: Summary::new(&intra),
+            inter: Summary::new(&inter),
+            overlap: overlap(&intra, &inter),
+            roc_auc: roc_auc(&intra, &inter),
+        }
+    }
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let weight = position - lower as f64;
+    sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+}
+
+pub fn overlap(intra: &[f64], inter: &[f64]) -> f64 {
+    if intra.is_empty() || inter.is_empty() {
+        return 0.0;
+    }
+
+    let values = intra.iter().chain(inter);
+    let min = values.clone().copied().fold(f64::INFINITY, f64::min);
+    let max = values.copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / HISTOGRAM_BINS as f64;
+
+    let histogram = |values: &[f64]| {
+        let mut bins = [0.0; HISTOGRAM_BINS];
+
+        for value in values {
+            let bin = if width > 0.0 {
+                (((value - min) / width) as usize).min(HISTOGRAM_BINS - 1)
+            } else {
+                0
+            };
+            bins[bin] += 1.0 / values.len() as f64;
+        }
+
+        bins
+    };
+
+    histogram(intra)
+        .iter()
+        .zip(histogram(inter).iter())
+        .map(|(a, b)| a.min(*b))
+        .sum()
+}
+
+pub fn roc_auc(intra: &[f64], inter: &[f64]) -> f64 {
+    if intra.is_empty() || inter.is_empty() {
+        return f64::NAN;
+    }
+
+    let wins = intra
+        .iter()
+        .flat_map(|a| inter.iter().map(move |b| (a, b)))
+        .map(|(a, b)| match a.total_cmp(b) {
+            std::cmp::Ordering::Less => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Greater => 0.0,
+        })
+        .sum::<f64>();
+    wins / (intra.len() * inter.len()) as f64
+}
+
+pub fn evaluate(dataset_name: &str, configurations: &[Configuration]) -> Vec<Separation> {
+    let dataset = Dataset::new(get_dataset_path(dataset_name)).expect("Failed to load dataset");
+    let entries = dataset.entries();
+    let pages = entries
+        .iter()
+        .map(|entry| entry.get_content().unwrap())
+        .collect::<Vec<String>>();
+    let labels = entries
+        .iter()
+        .map(|entry| entry.page_type.clone())
+        .collect::<Vec<String>>();
+
+    configurations
+        .iter()
+        .map(|configuration| {
+            let distances = configuration.distance_matrix(&configuration.page_inputs(&pages));
+            Separation::new(dataset_name, &configuration.name, &labels, &distances)
+        })
+        .collect()
+}
+
+pub fn print_table(separations: &[Separation]) {
+    println!(
+        "{:<16} {:<28} {:>10} {:>10} {:>10} {:>10} {:>8} {:>7}",
+        "Dataset",
+        "Configuration",
+        "Intra mean",
+        "Intra q75",
+        "Inter mean",
+        "Inter q25",
+        "Overlap",
+        "AUC"
+    );
+
+    for separation in separations {
+        println!(
+            "{:<16} {:<28} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>8.3} {:>7.3}",
+            separation.dataset,
+            separation.configuration,
+            separation.intra.mean,
+            separation.intra.q75,
+            separation.inter.mean,
+            separation.inter.q25,
+            separation.overlap,
+            separation.roc_auc
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_separation() {
+        let labels = ["movie", "movie", "video", "video"]
+            .iter()
+            .map(|label| label.to_string())
+            .collect::<Vec<String>>();
+        let square = [
+            [0.0, 0.1, 0.8, 0.7],
+            [0.1, 0.0, 0.9, 0.6],
+            [0.8, 0.9, 0.0, 0.2],
+            [0.7, 0.6, 0.2, 0.0],
+        ];
+        let distances = CondensedMatrix::from_fn(4, |a, b| square[a][b]);
+
+        let separation = Separation::new("test", "test", &labels, &distances);
+        assert_eq!(separation.intra.count, 2);
+        assert_eq!(separation.inter.count, 4);
+        assert_approx_eq!(separation.intra.mean, 0.15);
+        assert_approx_eq!(separation.inter.median, 0.75);
+        assert_approx_eq!(separation.roc_auc, 1.0);
+        assert_approx_eq!(separation.overlap, 0.0);
+    }
+
+    #[test]
+    fn test_roc_auc_ties() {
+        assert_approx_eq!(roc_auc(&[0.5, 0.5], &[0.5, 0.5]), 0.5);
+        assert_approx_eq!(roc_auc(&[0.9], &[0.1]), 0.0);
+    }
+}
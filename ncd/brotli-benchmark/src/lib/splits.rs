@@ -0,0 +1,259 @@
+use core::matrix::CondensedMatrix;
+use core::rng::Rng;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::configuration::Configuration;
+use crate::dataset::{Dataset, Entry, Split};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fold {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+impl Fold {
+    pub fn from_column(entries: &[Entry]) -> Self {
+        let (test, train) =
+            (0..entries.len()).partition(|&index| entries[index].split == Some(Split::Test));
+        Self { train, test }
+    }
+}
+
+fn strata(entries: &[Entry], seed: u64) -> Vec<Vec<usize>> {
+    let mut strata = BTreeMap::<&str, Vec<usize>>::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        strata.entry(&entry.page_type).or_default().push(index);
+    }
+
+    let mut rng = Rng::new(seed);
+    strata
+        .into_values()
+        .map(|mut indices| {
+            rng.shuffle(&mut indices);
+            indices
+        })
+        .collect()
+}
+
+pub fn train_test_split(entries: &[Entry], test_fraction: f64, seed: u64) -> Fold {
+    let mut fold = Fold {
+        train: Vec::new(),
+        test: Vec::new(),
+    };
+
+    for indices in strata(entries, seed) {
+        let test = ((indices.len() as f64 * test_fraction).round() as usize)
+            .min(indices.len().saturating_sub(1));
+        fold.test.extend_from_slice(&indices[..test]);
+        fold.train.extend_from_slice(&indices[test..]);
+    }
+
+    fold.train.sort_unstable();
+    fold.test.sort_unstable();
+    fold
+}
+
+pub fn k_fold(entries: &[Entry], k: usize, seed: u64) -> Vec<Fold> {
+    assert!(k >= 2, "k-fold cross-validation needs at least two folds");
+    let mut tests = vec![Vec::new(); k];
+    let mut next = 0;
+
+    for indices in strata(entries, seed) {
+        for index in indices {
+            tests[next % k].push(index);
+            next += 1;
+        }
+    }
+
+    tests
+        .into_iter()
+        .map(|mut test| {
+            test.sort_unstable();
+            let train = (0..entries.len())
+                .filter(|index| test.binary_search(index).is_err())
+                .collect();
+            Fold { train, test }
+        })
+        .collect()
+}
+
+pub fn nearest_template(
+    distances: &CondensedMatrix,
+    labels: &[String],
+    fold: &Fold,
+) -> Vec<String> {
+    fold.test
+        .iter()
+        .map(|&test| {
+            fold.train
+                .iter()
+                .min_by(|&&a, &&b| distances.get(test, a).total_cmp(&distances.get(test, b)))
+                .map_or_else(String::new, |&nearest| labels[nearest].clone())
+        })
+        .collect()
+}
+
+pub fn accuracy(distances: &CondensedMatrix, labels: &[String], fold: &Fold) -> f64 {
+    if fold.test.is_empty() {
+        return 0.0;
+    }
+
+    let correct = nearest_template(distances, labels, fold)
+        .iter()
+        .zip(&fold.test)
+        .filter(|(predicted, &test)| **predicted == labels[test])
+        .count();
+    correct as f64 / fold.test.len() as f64
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrossValidation {
+    pub dataset: String,
+    pub configuration: String,
+    pub seed: u64,
+    pub accuracies: Vec<f64>,
+    pub mean: f64,
+    pub standard_deviation: f64,
+}
+
+pub fn cross_validate(
+    dataset_name: &str,
+    dataset: &Dataset,
+    configurations: &[Configuration],
+    k: usize,
+    seed: u64,
+) -> Vec<CrossValidation> {
+    let entries = dataset.entries();
+    let labels = entries
+        .iter()
+        .map(|entry| entry.page_type.clone())
+        .collect::<Vec<String>>();
+    let folds = k_fold(entries, k, seed);
+
+    configurations
+        .iter()
+        .map(|configuration| {
+            let distances =
+                configuration.document_distance_matrix(&configuration.documents(entries));
+            let accuracies = folds
+                .iter()
+                .map(|fold| accuracy(&distances, &labels, fold))
+                .collect::<Vec<f64>>();
+            let mean = accuracies.iter().sum::<f64>() / accuracies.len() as f64;
+            let variance = accuracies
+                .iter()
+                .map(|accuracy| (accuracy - mean).powi(2))
+                .sum::<f64>()
+                / accuracies.len() as f64;
+
+            CrossValidation {
+                dataset: dataset_name.to_string(),
+                configuration: configuration.name.clone(),
+                seed,
+                accuracies,
+                mean,
+                standard_deviation: variance.sqrt(),
+            }
+        })
+        .collect()
+}
+
+pub fn print_table(results: &[CrossValidation]) {
+    println!(
+        "{:<16} {:<28} {:>6} {:>9} {:>9}",
+        "Dataset", "Configuration", "Folds", "Accuracy", "Std"
+    );
+
+    for result in results {
+        println!(
+            "{:<16} {:<28} {:>6} {:>9.3} {:>9.3}",
+            result.dataset,
+            result.configuration,
+            result.accuracies.len(),
+            result.mean,
+            result.standard_deviation
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entries(types: &[(&str, usize)]) -> Vec<Entry> {
+        types
+            .iter()
+            .flat_map(|&(page_type, count)| (0..count).map(move |index| (page_type, index)))
+            .enumerate()
+            .map(|(line, (page_type, index))| Entry {
+                line: line + 2,
+                url: format!("https://example.com/{page_type}/{index}"),
+                page_type: page_type.to_string(),
+                filepath: PathBuf::new(),
+                sha256: None,
+                fetched_at: None,
+                site: None,
+                split: None,
+                notes: None,
+                archive: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stratified_splits() {
+        let entries = entries(&[("article", 10), ("video", 5), ("forum", 1)]);
+        let count = |indices: &[usize], page_type: &str| {
+            indices
+                .iter()
+                .filter(|&&index| entries[index].page_type == page_type)
+                .count()
+        };
+
+        let split = train_test_split(&entries, 0.2, 7);
+        assert_eq!(split, train_test_split(&entries, 0.2, 7));
+        assert_ne!(split, train_test_split(&entries, 0.2, 8));
+        assert_eq!(count(&split.test, "article"), 2);
+        assert_eq!(count(&split.test, "video"), 1);
+        assert_eq!(count(&split.test, "forum"), 0);
+        assert_eq!(split.train.len() + split.test.len(), entries.len());
+
+        let folds = k_fold(&entries, 5, 7);
+        let mut tested = folds
+            .iter()
+            .flat_map(|fold| fold.test.clone())
+            .collect::<Vec<usize>>();
+        tested.sort_unstable();
+        assert_eq!(tested, (0..entries.len()).collect::<Vec<usize>>());
+
+        for fold in &folds {
+            assert_eq!(count(&fold.test, "article"), 2);
+            assert_eq!(fold.train.len() + fold.test.len(), entries.len());
+            assert!(fold.test.len() <= 4);
+        }
+    }
+
+    #[test]
+    fn test_nearest_template() {
+        let labels = ["movie", "movie", "video", "video"].map(str::to_string);
+        let distances = CondensedMatrix::from_square(&[
+            vec![0.0, 0.1, 0.9, 0.8],
+            vec![0.1, 0.0, 0.9, 0.9],
+            vec![0.9, 0.9, 0.0, 0.2],
+            vec![0.8, 0.9, 0.2, 0.0],
+        ]);
+        let fold = Fold {
+            train: vec![1, 2],
+            test: vec![0, 3],
+        };
+
+        assert_eq!(
+            nearest_template(&distances, &labels, &fold),
+            ["movie", "video"]
+        );
+        assert_eq!(accuracy(&distances, &labels, &fold), 1.0);
+    }
+}
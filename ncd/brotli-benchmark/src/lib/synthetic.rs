@@ -0,0 +1,493 @@
+use core::rng::Rng;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::{fs, io};
+
+use crate::url_path::url_to_path;
+
+const HOST: &str = "https://synthetic.test";
+const WORDS: [&str; 24] = [
+    "jurassic", "river", "market", "season", "signal", "orbit", "garden", "vector", "harbor",
+    "canyon", "lantern", "meadow", "quartz", "summit", "ember", "glacier", "violet", "timber",
+    "cobalt", "falcon", "prairie", "saffron", "tundra", "willow",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Template {
+    Article,
+    ProductList,
+    Video,
+    Forum,
+}
+
+impl Template {
+    pub fn all() -> Vec<Self> {
+        vec![Self::Article, Self::ProductList, Self::Video, Self::Forum]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Article => "article",
+            Self::ProductList => "product_list",
+            Self::Video => "video",
+            Self::Forum => "forum",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Perturbation {
+    pub list_length: RangeInclusive<usize>,
+    pub randomize_classes: bool,
+    pub ad_probability: f64,
+}
+
+impl Perturbation {
+    pub fn none() -> Self {
+        Self {
+            list_length: 5..=5,
+            randomize_classes: false,
+            ad_probability: 0.0,
+        }
+    }
+
+    pub fn recommended() -> Self {
+        Self {
+            list_length: 3..=12,
+            randomize_classes: true,
+            ad_probability: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticPage {
+    pub url: String,
+    pub template: Template,
+    pub html: String,
+}
+
+struct Page<'a> {
+    rng: &'a mut Rng,
+    perturbation: &'a Perturbation,
+    suffix: String,
+    html: String,
+}
+
+impl Page<'_> {
+    fn class(&self, name: &str) -> String {
+        format!("class=\"{name}{}\"", self.suffix)
+    }
+
+    fn words(&mut self, count: usize) -> String {
+        (0..count)
+            .map(|_| WORDS[self.rng.below(WORDS.len())])
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    fn list_length(&mut self) -> usize {
+        let range = &self.perturbation.list_length;
+        range.start() + self.rng.below(range.end() - range.start() + 1)
+    }
+
+    fn open(&mut self, tag: &str, class: &str) {
+        let class = self.class(class);
+        write!(self.html, "<{tag} {class}>").unwrap();
+    }
+
+    fn close(&mut self, tag: &str) {
+        write!(self.html, "</{tag}>").unwrap();
+    }
+
+    fn element(&mut self, tag: &str, class: &str, words: usize) {
+        let text = self.words(words);
+        self.open(tag, class);
+        self.html.push_str(&text);
+        self.close(tag);
+    }
+
+    fn maybe_ad(&mut self) {
+        if self.rng.chance(self.perturbation.ad_probability) {
+            let id = self.rng.next_u64() % 100_000;
+            write!(
+                self.html,
+                "<div class=\"ad-slot\" id=\"ad-{id}\"><iframe src=\"https://ads.synthetic.test/{id}\"></iframe><span>Advertisement</span></div>"
+            )
+            .unwrap();
+        }
+    }
+
+    fn header(&mut self) {
+        self.open("header", "site-header");
+        self.open("a", "logo");
+        self.html.push_str("Synthetic");
+        self.close("a");
+        self.open("nav", "main-nav");
+        self.open("ul", "nav-list");
+
+        for _ in 0..5 {
+            self.open("li", "nav-item");
+            self.element("a", "nav-link", 1);
+            self.close("li");
+        }
+
+        self.close("ul");
+        self.close("nav");
+        self.close("header");
+    }
+
+    fn footer(&mut self) {
+        self.open("footer", "site-footer");
+        self.element("p", "copyright", 4);
+        self.close("footer");
+    }
+
+    fn article(&mut self) {
+        self.open("main", "content");
+        self.open("article", "story");
+        self.element("h1", "headline", 6);
+        self.open("div", "byline");
+        self.element("span", "author", 2);
+        self.element("time", "published", 1);
+        self.close("div");
+        self.open("figure", "lead-image");
+        self.html.push_str("<img src=\"/lead.jpg\">");
+        self.element("figcaption", "caption", 8);
+        self.close("figure");
+
+        for _ in 0..self.list_length() {
+            self.element("p", "paragraph", 40);
+            self.maybe_ad();
+        }
+
+        self.close("article");
+        self.open("aside", "related");
+        self.element("h2", "related-title", 2);
+        self.open("ul", "related-list");
+
+        for _ in 0..4 {
+            self.open("li", "related-item");
+            self.element("a", "related-link", 5);
+            self.close("li");
+        }
+
+        self.close("ul");
+        self.close("aside");
+        self.close("main");
+    }
+
+    fn product_list(&mut self) {
+        self.open("main", "listing");
+        self.open("form", "search");
+        self.html
+            .push_str("<input type=\"search\" name=\"q\"><button>Search</button>");
+        self.close("form");
+        self.open("div", "filters");
+
+        for _ in 0..4 {
+            self.open("label", "filter");
+            let text = self.words(1);
+            self.html.push_str("<input type=\"checkbox\">");
+            self.html.push_str(&text);
+            self.close("label");
+        }
+
+        self.close("div");
+        self.open("div", "product-grid");
+
+        for _ in 0..self.list_length() {
+            self.open("div", "product-card");
+            self.html.push_str("<img src=\"/product.jpg\">");
+            self.open("h2", "product-title");
+            self.element("a", "product-link", 4);
+            self.close("h2");
+            self.open("div", "rating");
+
+            for _ in 0..5 {
+                self.element("span", "star", 0);
+            }
+
+            self.close("div");
+            let price = self.rng.below(10_000);
+            self.open("span", "price");
+            write!(self.html, "${}.{:02}", price / 100, price % 100).unwrap();
+            self.close("span");
+            self.element("button", "add-to-cart", 3);
+            self.close("div");
+            self.maybe_ad();
+        }
+
+        self.close("div");
+        self.open("nav", "pagination");
+
+        for page in 1..=5 {
+            self.open("a", "page");
+            write!(self.html, "{page}").unwrap();
+            self.close("a");
+        }
+
+        self.close("nav");
+        self.close("main");
+    }
+
+    fn video(&mut self) {
+        self.open("main", "watch");
+        self.open("div", "player");
+        self.html.push_str(
+            "<video><source src=\"/video.mp4\" type=\"video/mp4\"><track kind=\"captions\"></video>",
+        );
+        self.open("div", "controls");
+
+        for _ in 0..4 {
+            self.element("button", "control", 1);
+        }
+
+        self.html.push_str("<input type=\"range\">");
+        self.close("div");
+        self.close("div");
+        self.element("h1", "video-title", 5);
+        self.open("div", "channel");
+        self.html.push_str("<img src=\"/avatar.jpg\">");
+        self.element("a", "channel-name", 2);
+        self.element("button", "subscribe", 1);
+        self.close("div");
+        self.open("section", "comments");
+
+        for _ in 0..self.list_length() {
+            self.open("div", "comment");
+            self.html.push_str("<img src=\"/avatar.jpg\">");
+            self.open("div", "comment-body");
+            self.element("a", "comment-author", 1);
+            self.element("p", "comment-text", 12);
+            self.close("div");
+            self.open("div", "comment-actions");
+            self.element("button", "like", 1);
+            self.element("button", "reply", 1);
+            self.close("div");
+            self.close("div");
+            self.maybe_ad();
+        }
+
+        self.close("section");
+        self.open("aside", "up-next");
+
+        for _ in 0..3 {
+            self.open("a", "video-card");
+            self.html.push_str("<img src=\"/thumbnail.jpg\">");
+            self.element("span", "duration", 1);
+            self.element("h3", "card-title", 4);
+            self.close("a");
+        }
+
+        self.close("aside");
+        self.close("main");
+    }
+
+    fn forum(&mut self) {
+        self.open("main", "board");
+        self.open("ol", "breadcrumbs");
+
+        for _ in 0..3 {
+            self.open("li", "crumb");
+            self.element("a", "crumb-link", 1);
+            self.close("li");
+        }
+
+        self.close("ol");
+        self.open("table", "threads");
+        self.html.push_str("<thead><tr>");
+
+        for heading in ["Topic", "Author", "Replies", "Last post"] {
+            self.open("th", "column");
+            self.html.push_str(heading);
+            self.close("th");
+        }
+
+        self.html.push_str("</tr></thead><tbody>");
+
+        for _ in 0..self.list_length() {
+            self.open("tr", "thread");
+            self.open("td", "topic");
+            self.element("a", "thread-link", 6);
+            self.close("td");
+            self.element("td", "thread-author", 1);
+            let replies = self.rng.below(500);
+            self.open("td", "replies");
+            write!(self.html, "{replies}").unwrap();
+            self.close("td");
+            self.element("td", "last-post", 2);
+            self.close("tr");
+            self.maybe_ad();
+        }
+
+        self.html.push_str("</tbody>");
+        self.close("table");
+        self.open("form", "reply-form");
+        self.html
+            .push_str("<textarea name=\"message\"></textarea><button>Post</button>");
+        self.close("form");
+        self.close("main");
+    }
+}
+
+pub struct Generator {
+    templates: Vec<Template>,
+    perturbation: Perturbation,
+    rng: Rng,
+}
+
+impl Generator {
+    pub fn new(templates: Vec<Template>, perturbation: Perturbation, seed: u64) -> Self {
+        Self {
+            templates,
+            perturbation,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn recommended(seed: u64) -> Self {
+        Self::new(Template::all(), Perturbation::recommended(), seed)
+    }
+
+    pub fn page(&mut self, template: Template, index: usize) -> SyntheticPage {
+        let suffix = if self.perturbation.randomize_classes {
+            format!("-{:06x}", self.rng.next_u64() & 0xff_ffff)
+        } else {
+            String::new()
+        };
+        let mut page = Page {
+            rng: &mut self.rng,
+            perturbation: &self.perturbation,
+            suffix,
+            html: String::new(),
+        };
+
+        page.html
+            .push_str("<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>");
+        let title = page.words(4);
+        page.html.push_str(&title);
+        page.html.push_str("</title></head><body>");
+        page.header();
+
+        match template {
+            Template::Article => page.article(),
+            Template::ProductList => page.product_list(),
+            Template::Video => page.video(),
+            Template::Forum => page.forum(),
+        }
+
+        page.footer();
+        page.html.push_str("</body></html>");
+
+        SyntheticPage {
+            url: format!("{HOST}/{}/{index}", template.name()),
+            template,
+            html: page.html,
+        }
+    }
+
+    pub fn generate(&mut self, pages_per_template: usize) -> Vec<SyntheticPage> {
+        let templates = self.templates.clone();
+
+        (0..pages_per_template)
+            .flat_map(|index| templates.iter().map(move |&template| (template, index)))
+            .map(|(template, index)| self.page(template, index))
+            .collect()
+    }
+}
+
+pub fn write_dataset<P: AsRef<Path>>(directory: P, pages: &[SyntheticPage]) -> io::Result<()> {
+    let directory = directory.as_ref();
+    let mut csv = "url, type\n".to_string();
+
+    for page in pages {
+        let filepath = directory.join(url_to_path(&page.url));
+        fs::create_dir_all(filepath.parent().unwrap_or(directory))?;
+        fs::write(filepath, &page.html)?;
+        csv += &format!("\"{}\", \"{}\"\n", page.url, page.template.name());
+    }
+
+    fs::write(directory.join("dataset.csv"), csv)
+}
+
+// Writes a generated dataset into a temporary directory that is removed when the returned
+// guard is dropped.
+#[cfg(test)]
+pub fn temp_dataset(
+    seed: u64,
+    pages_per_template: usize,
+) -> (tempfile::TempDir, crate::dataset::Dataset) {
+    let temp = tempfile::tempdir().unwrap();
+    let directory = temp.path().join("synthetic");
+    write_dataset(
+        &directory,
+        &Generator::recommended(seed).generate(pages_per_template),
+    )
+    .unwrap();
+    let dataset = crate::dataset::Dataset::new(&directory).unwrap();
+    (temp, dataset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clustering::average_linkage;
+    use crate::configuration::Configuration;
+    use crate::evaluation::Evaluation;
+    use crate::splits::{accuracy, k_fold};
+
+    #[test]
+    fn test_deterministic_pages() {
+        let pages = Generator::recommended(1).generate(3);
+
+        assert_eq!(pages, Generator::recommended(1).generate(3));
+        assert_ne!(pages, Generator::recommended(2).generate(3));
+        assert_eq!(pages.len(), 12);
+        assert_eq!(pages[1].url, "https://synthetic.test/product_list/0");
+        assert_eq!(pages[1].template, Template::ProductList);
+
+        let plain = Generator::new(vec![Template::Forum], Perturbation::none(), 1).generate(2);
+        assert!(!plain[0].html.contains("ad-slot"));
+        assert!(plain[0].html.contains("class=\"thread\""));
+        assert_eq!(plain[0].html.matches("<tr class=\"thread\">").count(), 5);
+    }
+
+    #[test]
+    fn test_clustering_and_classification() {
+        let (_temp, dataset) = temp_dataset(42, 6);
+        let entries = dataset.entries();
+        let labels = entries
+            .iter()
+            .map(|entry| entry.page_type.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(entries.len(), 24);
+
+        for configuration in Configuration::all()
+            .into_iter()
+            .chain(Configuration::tokenized())
+        {
+            let distances =
+                configuration.document_distance_matrix(&configuration.documents(entries));
+            let assignments = average_linkage(&distances, Template::all().len());
+            let evaluation = Evaluation::new(
+                "synthetic",
+                &configuration.name,
+                &labels,
+                &assignments,
+                &distances,
+            );
+            assert!(
+                evaluation.adjusted_rand_index > 0.9,
+                "{}: {}",
+                configuration.name,
+                evaluation.adjusted_rand_index
+            );
+
+            for fold in k_fold(entries, 3, 42) {
+                assert_eq!(accuracy(&distances, &labels, &fold), 1.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+const INDEX: &str = "index.html";
+const EXTENSION: &str = ".html";
+
+pub fn url_to_path(url: &str) -> String {
+    let without_protocol = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_fragment = without_protocol
+        .split_once('#')
+        .map_or(without_protocol, |(rest, _)| rest);
+    let path = match without_fragment.find(['/', '?']) {
+        Some(index) => without_fragment[index..].trim_start_matches('/'),
+        None => "",
+    };
+
+    if path.is_empty() || path.ends_with('/') {
+        format!("{path}{INDEX}")
+    } else {
+        format!("{path}{EXTENSION}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv;
+
+    #[test]
+    fn test_url_fixtures() {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/../../dataset/url_paths.csv");
+        let text = std::fs::read_to_string(fixtures).expect("Failed to read URL fixtures");
+        let records = csv::parse(&text).unwrap();
+
+        assert!(records.len() > 1);
+
+        for record in &records[1..] {
+            assert_eq!(
+                url_to_path(&record.fields[0]),
+                record.fields[1],
+                "Wrong path for {} on line {}",
+                record.fields[0],
+                record.line
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::dataset::Dataset;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub line: usize,
+    pub url: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    pub entries: usize,
+    pub missing: Vec<Issue>,
+    pub empty: Vec<Issue>,
+    pub unreadable: Vec<Issue>,
+    pub duplicates: Vec<Issue>,
+    pub unknown_types: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn new(dataset: &Dataset) -> Self {
+        let mut report = Self {
+            entries: dataset.entries().len() + dataset.missing().len(),
+            ..Self::default()
+        };
+
+        for missing in dataset.missing() {
+            report.missing.push(Issue {
+                line: missing.line,
+                url: missing.url.clone(),
+                detail: "No file found for URL".to_string(),
+            });
+        }
+
+        for entry in dataset.entries() {
+            let issue = |detail: String| Issue {
+                line: entry.line,
+                url: entry.url.clone(),
+                detail,
+            };
+
+            match entry.get_bytes() {
+                Ok(content) if content.is_empty() => report
+                    .empty
+                    .push(issue(format!("{} is empty", entry.filepath.display()))),
+                Ok(content) => {
+                    if let Err(error) = std::str::from_utf8(&content) {
+                        report.unreadable.push(issue(format!(
+                            "{} is not valid UTF-8: {}",
+                            entry.filepath.display(),
+                            error
+                        )));
+                    }
+                }
+                Err(error) => report.unreadable.push(issue(format!(
+                    "{}: {}",
+                    entry.filepath.display(),
+                    error
+                ))),
+            }
+        }
+
+        let mut rows = dataset
+            .entries()
+            .iter()
+            .map(|entry| (entry.line, &entry.url, &entry.page_type))
+            .chain(
+                dataset
+                    .missing()
+                    .iter()
+                    .map(|missing| (missing.line, &missing.url, &missing.page_type)),
+            )
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|&(line, _, _)| line);
+        let mut first_lines = BTreeMap::new();
+
+        for &(line, url, page_type) in &rows {
+            if let Some(first) = first_lines.get(url) {
+                report.duplicates.push(Issue {
+                    line,
+                    url: url.clone(),
+                    detail: format!("Duplicate of line {first}"),
+                });
+            } else {
+                first_lines.insert(url, line);
+            }
+
+            if !dataset.page_types().contains(page_type) {
+                report.unknown_types.push(Issue {
+                    line,
+                    url: url.clone(),
+                    detail: format!("Unknown page type '{page_type}'"),
+                });
+            }
+        }
+
+        report
+    }
+
+    pub fn problems(&self) -> usize {
+        self.missing.len()
+            + self.empty.len()
+            + self.unreadable.len()
+            + self.duplicates.len()
+            + self.unknown_types.len()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.problems() == 0
+    }
+
+    pub fn print(&self, name: &str) {
+        println!(
+            "{name}: {} rows, {} problems",
+            self.entries,
+            self.problems()
+        );
+
+        for (kind, issues) in [
+            ("missing", &self.missing),
+            ("empty", &self.empty),
+            ("unreadable", &self.unreadable),
+            ("duplicate", &self.duplicates),
+            ("unknown type", &self.unknown_types),
+        ] {
+            for issue in issues {
+                println!(
+                    "  line {}: {kind}: {} ({})",
+                    issue.line, issue.url, issue.detail
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_validation_report() {
+        let temp = tempfile::tempdir().unwrap();
+        let directory = temp.path().join("validation");
+        fs::create_dir_all(directory.join("wiki")).unwrap();
+        fs::write(directory.join("wiki/Full.html"), "<html></html>").unwrap();
+        fs::write(directory.join("wiki/Empty.html"), "").unwrap();
+        fs::write(directory.join("wiki/Binary.html"), [0xff, 0xfe, 0x00]).unwrap();
+        fs::write(directory.join("page_types.csv"), "type\narticle\n").unwrap();
+        fs::write(
+            directory.join("dataset.csv"),
+            "url, type, path\n\
+             https://en.wikipedia.org/wiki/Full, article, \n\
+             https://en.wikipedia.org/wiki/Empty, article, \n\
+             https://en.wikipedia.org/wiki/Binary, gallery, \n\
+             https://en.wikipedia.org/wiki/Absent, article, \n\
+             https://en.wikipedia.org/wiki/Full, article, \n\
+             https://en.wikipedia.org/wiki/Absent, article, wiki/Full.html\n",
+        )
+        .unwrap();
+
+        let dataset = Dataset::new(&directory).unwrap();
+        let report = ValidationReport::new(&dataset);
+
+        assert_eq!(report.entries, 6);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].line, 5);
+        assert_eq!(report.empty.len(), 1);
+        assert_eq!(report.unreadable.len(), 1);
+        assert_eq!(report.duplicates.len(), 2);
+        assert_eq!(report.duplicates[0].line, 6);
+        assert_eq!(report.duplicates[1].line, 7);
+        assert_eq!(report.duplicates[1].detail, "Duplicate of line 5");
+        assert_eq!(report.unknown_types.len(), 1);
+        assert_eq!(report.unknown_types[0].line, 4);
+        assert!(!report.is_valid());
+    }
+}
@@ -1,74 +1,256 @@
 use benchmark::benchmarks::distance_matrix::heatmap;
-use benchmark::benchmarks::triangle_inequality;
-use benchmark::dataset;
-use core::calculate;
-use core::compress::{brotli::CompressBrotli, Compressor};
-use plotly::common::{AxisSide, Title};
-use plotly::layout::Axis;
-use plotly::{HeatMap, Layout, Plot};
-use rayon::prelude::*;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
-
-fn read_from_file(file_path: &str) -> String {
-    let project_root = env!("CARGO_MANIFEST_DIR");
-    let full_path = std::path::Path::new(project_root).join(file_path);
-    std::fs::read_to_string(full_path).expect("Failed to read file")
-}
+use benchmark::benchmarks::lsh_prefilter::lsh_prefilter;
+use benchmark::benchmarks::metric_axioms::{metric_axioms, Tolerance};
+use benchmark::benchmarks::{get_dataset_path, DATASETS};
+use benchmark::configuration::Configuration;
+use benchmark::dataset::Dataset;
+use benchmark::grid_search::{self, GridSearch};
+use benchmark::manifest;
+use benchmark::validation::ValidationReport;
+use benchmark::{distances, evaluation, save_json, separation, splits};
+use core::lsh::Prefilter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const FOLDS: usize = 5;
+const SEED: u64 = 42;
+
+const USAGE: &str = "Usage:
+    benchmark                              Run all benchmarks
+    benchmark validate [--strict] [DATASET...]
+                                           Report missing, empty or unreadable files,
+                                           duplicate URLs and unknown page types
+    benchmark manifest [DATASET...]        Write manifest.json with the path, size and SHA-256 of
+                                           every URL and report pages whose content changed
+    benchmark matrix [DATASET...]          Update results/matrices/DATASET.bin, recomputing only
+                                           the distances of added or changed pages
 
-const FILE_PATH: &str = "../../dataset/imdb/list/ls541382956/?ref_=tt_urls_2.html";
+DATASET is a dataset name, a directory or a .tar.zst, .zip or .warc(.gz) archive";
 
-#[derive(Debug, Clone)]
-struct BenchmarkResult {
-    quality: u32,
-    lg_window_size: u32,
-    compression_ratio: f64,
-    duration: Duration,
+fn grid_search() {
+    let results = GridSearch::recommended().run(&DATASETS);
+    grid_search::print_table(&results);
+    save_json(&results, "results/grid_search.json").expect("Failed to save grid search results");
 }
 
-fn same_page() {
-    println!("A distance betwwen the same pages should be close to 0.0");
-    let page_html = read_from_file(FILE_PATH);
-    let mut results = Vec::new();
-
-    for quality in 3..11 {
-        for lg_window_size in 20..=22 {
-            let start = Instant::now();
-            let compressor = CompressBrotli::new(quality, lg_window_size);
-            let result = compressor.get_distance(&page_html, &page_html);
-            let duration = start.elapsed();
-
-            let benchmark_result = BenchmarkResult {
-                quality,
-                lg_window_size,
-                compression_ratio: result,
-                duration,
+fn dataset_arguments<'a>(
+    args: &'a [String],
+    options: &[&str],
+) -> Result<Vec<(&'a str, PathBuf)>, ExitCode> {
+    if let Some(unknown) = args
+        .iter()
+        .find(|arg| arg.starts_with("--") && !options.contains(&arg.as_str()))
+    {
+        eprintln!("Unknown option: {unknown}\n{USAGE}");
+        return Err(ExitCode::FAILURE);
+    }
+
+    let mut names = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .map(String::as_str)
+        .collect::<Vec<&str>>();
+
+    if names.is_empty() {
+        names = DATASETS.to_vec();
+    }
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let directory = if PathBuf::from(name).exists() {
+                PathBuf::from(name)
+            } else {
+                get_dataset_path(name)
             };
+            (name, directory)
+        })
+        .collect())
+}
+
+fn validate(args: &[String]) -> ExitCode {
+    let datasets = match dataset_arguments(args, &["--strict"]) {
+        Ok(datasets) => datasets,
+        Err(code) => return code,
+    };
+    let strict = args.iter().any(|arg| arg == "--strict");
+    let mut problems = 0;
+    let mut failed = false;
+
+    for (name, directory) in datasets {
+        match Dataset::new(&directory) {
+            Ok(dataset) => {
+                let report = ValidationReport::new(&dataset);
+                report.print(name);
+                problems += report.problems();
+            }
+            Err(error) => {
+                eprintln!("{name}: {error}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed || (strict && problems > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn manifest(args: &[String]) -> ExitCode {
+    let datasets = match dataset_arguments(args, &[]) {
+        Ok(datasets) => datasets,
+        Err(code) => return code,
+    };
+    let mut failed = false;
+
+    for (name, directory) in datasets {
+        match manifest::update(&directory) {
+            Ok(changed) => {
+                println!(
+                    "{name}: wrote {}",
+                    directory.join(manifest::FILE_NAME).display()
+                );
+                for url in changed {
+                    println!("{name}: changed {url}");
+                }
+            }
+            Err(error) => {
+                eprintln!("{name}: {error}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
 
-            results.push(benchmark_result.clone());
+fn matrix(args: &[String]) -> ExitCode {
+    let datasets = match dataset_arguments(args, &[]) {
+        Ok(datasets) => datasets,
+        Err(code) => return code,
+    };
+    let configuration = Configuration::recommended();
+    let mut failed = false;
 
-            println!(
-                "Quality: {quality}, LG Window Size: {lg_window_size}, Distance: {:.6}, Time: {:?}",
-                result, duration
-            );
+    for (name, directory) in datasets {
+        let path = Path::new("results/matrices").join(format!(
+            "{}.bin",
+            directory.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        let result = Dataset::new(&directory).and_then(|dataset| {
+            configuration
+                .incremental_distance_matrix(&configuration.documents(dataset.entries()), &path)
+                .map_err(Into::into)
+        });
+
+        match result {
+            Ok((_, update)) => println!(
+                "{name}: {} kept, {} added, {} removed, {} distances computed, wrote {}",
+                update.kept,
+                update.added,
+                update.removed,
+                update.computed,
+                path.display()
+            ),
+            Err(error) => {
+                eprintln!("{name}: {error}");
+                failed = true;
+            }
         }
     }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-fn main() {
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    match args.first().map(String::as_str) {
+        Some("validate") => return validate(&args[1..]),
+        Some("manifest") => return manifest(&args[1..]),
+        Some("matrix") => return matrix(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(command) => {
+            eprintln!("Unknown command: {command}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+        None => {}
+    }
+
     println!("NCD Brotli Benchmark");
-    same_page();
+    grid_search();
 
     let cache = &mut benchmark::benchmarks::Cache::new();
+    let prefilter = Prefilter::recommended();
+    let recommended = Configuration::recommended();
+    let configurations = Configuration::all();
+    let mut evaluations = Vec::new();
+    let mut separations = Vec::new();
+    let mut metric_reports = Vec::new();
+    let mut distance_comparisons = Vec::new();
 
-    for dataset in ["euronews.com", "amazon", "imdb", "wikipedia"] {
-        heatmap(cache, dataset);
-        triangle_inequality::triangle_inequality(cache, dataset);
+    for dataset in DATASETS {
+        heatmap(cache, &recommended, dataset);
+        for configuration in &configurations {
+            metric_reports.push(metric_axioms(
+                dataset,
+                configuration,
+                Tolerance::recommended(),
+            ));
+        }
+        lsh_prefilter(cache, &recommended, dataset, &prefilter, 0.2);
+        evaluations.extend(evaluation::evaluate(dataset, &configurations));
+        separations.extend(separation::evaluate(dataset, &configurations));
+        distance_comparisons.extend(distances::compare(dataset, &Configuration::recommended()));
     }
+
+    evaluation::print_table(&evaluations);
+    save_json(&evaluations, "results/evaluation.json").expect("Failed to save evaluation results");
+    separation::print_table(&separations);
+    save_json(&separations, "results/separation.json").expect("Failed to save separation results");
+    save_json(&metric_reports, "results/metric_axioms.json")
+        .expect("Failed to save metric axiom reports");
+    distances::print_table(&distance_comparisons);
+    save_json(&distance_comparisons, "results/distances.json")
+        .expect("Failed to save distance comparisons");
+
+    let combined = Dataset::merge(
+        DATASETS
+            .iter()
+            .map(|name| Dataset::new(get_dataset_path(name)).expect("Failed to load dataset"))
+            .collect(),
+    );
+    let cross_validation =
+        splits::cross_validate("combined", &combined, &configurations, FOLDS, SEED);
+    splits::print_table(&cross_validation);
+    save_json(&cross_validation, "results/cross_validation.json")
+        .expect("Failed to save cross-validation results");
+
+    ExitCode::SUCCESS
 }
 
 #[test]
-fn test_heatmap() {
-    let mut cache = benchmark::benchmarks::Cache::new();
-    heatmap(&mut cache, "euronews.com");
+fn test_dataset_arguments() {
+    let args = ["--strict", "imdb"].map(String::from);
+    let datasets = dataset_arguments(&args, &["--strict"]).unwrap();
+    assert_eq!(datasets.len(), 1);
+    assert_eq!(datasets[0].0, "imdb");
+
+    let args = ["--strcit", "imdb"].map(String::from);
+    assert!(dataset_arguments(&args, &["--strict"]).is_err());
+    assert!(validate(&args) == ExitCode::FAILURE);
+    assert!(manifest(&["--foo".to_string()]) == ExitCode::FAILURE);
 }
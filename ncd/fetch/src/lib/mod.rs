@@ -0,0 +1,473 @@
+pub mod rate_limit;
+
+use benchmark::dataset::{Dataset, Error};
+use benchmark::manifest::{relative_path, Manifest, ManifestEntry};
+use rate_limit::RateLimiter;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fs, thread};
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub concurrency: usize,
+    pub retries: u32,
+    pub backoff: Duration,
+    pub interval: Duration,
+    pub timeout: Duration,
+    pub user_agent: String,
+    pub force: bool,
+}
+
+impl Options {
+    pub fn recommended() -> Self {
+        Self {
+            concurrency: 4,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            interval: Duration::from_millis(250),
+            timeout: Duration::from_secs(30),
+            user_agent: concat!("optics-fetch/", env!("CARGO_PKG_VERSION")).to_string(),
+            force: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Fetched {
+        status: u16,
+        size: usize,
+    },
+    Skipped,
+    Failed {
+        status: Option<u16>,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub line: usize,
+    pub url: String,
+    pub filepath: PathBuf,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchError {
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+struct Job {
+    line: usize,
+    url: String,
+    path: String,
+    filepath: PathBuf,
+}
+
+pub struct Fetcher {
+    agent: ureq::Agent,
+    options: Options,
+    limiter: RateLimiter,
+}
+
+impl Fetcher {
+    pub fn new(options: Options) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .user_agent(&options.user_agent)
+                .timeout(options.timeout)
+                .build(),
+            limiter: RateLimiter::new(options.interval),
+            options,
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Result<(u16, Vec<u8>), FetchError> {
+        let mut error = FetchError {
+            status: None,
+            message: "No attempt made".to_string(),
+        };
+
+        for attempt in 0..=self.options.retries {
+            if attempt > 0 {
+                thread::sleep(self.options.backoff * 2u32.pow(attempt - 1));
+            }
+
+            self.limiter.wait();
+
+            match self.agent.get(url).call() {
+                Ok(response) => {
+                    let status = response.status();
+                    let mut body = Vec::new();
+
+                    match response.into_reader().read_to_end(&mut body) {
+                        Ok(_) => return Ok((status, body)),
+                        Err(read_error) => {
+                            error = FetchError {
+                                status: Some(status),
+                                message: read_error.to_string(),
+                            }
+                        }
+                    }
+                }
+                Err(ureq::Error::Status(status, response)) => {
+                    error = FetchError {
+                        status: Some(status),
+                        message: response.status_text().to_string(),
+                    };
+
+                    if !is_retryable(status) {
+                        break;
+                    }
+                }
+                Err(ureq::Error::Transport(transport)) => {
+                    error = FetchError {
+                        status: None,
+                        message: transport.to_string(),
+                    }
+                }
+            }
+        }
+
+        Err(error)
+    }
+
+    pub fn fetch_dataset<P: AsRef<Path>>(&self, directory: P) -> Result<Vec<Report>, Error> {
+        let directory = directory.as_ref();
+
+        if !directory.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", directory.display()),
+            )
+            .into());
+        }
+
+        let dataset = Dataset::new(directory)?;
+        let mut reports = Vec::new();
+        let mut jobs = dataset
+            .missing()
+            .iter()
+            .map(|missing| Job {
+                line: missing.line,
+                url: missing.url.clone(),
+                path: relative_path(directory, &missing.filepath, &missing.url),
+                filepath: missing.filepath.clone(),
+            })
+            .collect::<Vec<Job>>();
+
+        for entry in dataset.entries() {
+            if self.options.force {
+                jobs.push(Job {
+                    line: entry.line,
+                    url: entry.url.clone(),
+                    path: relative_path(directory, &entry.filepath, &entry.url),
+                    filepath: entry.filepath.clone(),
+                });
+            } else {
+                reports.push(Report {
+                    line: entry.line,
+                    url: entry.url.clone(),
+                    filepath: entry.filepath.clone(),
+                    outcome: Outcome::Skipped,
+                });
+            }
+        }
+
+        let next = AtomicUsize::new(0);
+        let fetched = Mutex::new(Vec::new());
+
+        thread::scope(|scope| {
+            for _ in 0..self.options.concurrency.max(1) {
+                scope.spawn(|| {
+                    while let Some(job) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = self.fetch_job(job);
+                        fetched.lock().unwrap().push(result);
+                    }
+                });
+            }
+        });
+
+        let mut manifest = Manifest::load(directory)?.unwrap_or_default();
+
+        for (report, recorded) in fetched.into_inner().unwrap() {
+            if let Some(recorded) = recorded {
+                let previous = manifest.get(&recorded.url);
+
+                if recorded.sha256.is_some() || previous.is_none_or(|entry| entry.sha256.is_none())
+                {
+                    manifest.upsert(recorded);
+                }
+            }
+
+            reports.push(report);
+        }
+
+        manifest.save(directory)?;
+        reports.sort_by_key(|report| report.line);
+        Ok(reports)
+    }
+
+    fn fetch_job(&self, job: &Job) -> (Report, Option<ManifestEntry>) {
+        let fetched_at = Some(timestamp(SystemTime::now()));
+        let report = |outcome| Report {
+            line: job.line,
+            url: job.url.clone(),
+            filepath: job.filepath.clone(),
+            outcome,
+        };
+
+        match self.get(&job.url) {
+            Ok((status, body)) => match write_atomically(&job.filepath, &body) {
+                Ok(()) => {
+                    let mut recorded = ManifestEntry::new(&job.url, &job.path).with_content(&body);
+                    recorded.status = Some(status);
+                    recorded.fetched_at = fetched_at;
+                    (
+                        report(Outcome::Fetched {
+                            status,
+                            size: body.len(),
+                        }),
+                        Some(recorded),
+                    )
+                }
+                Err(error) => (
+                    report(Outcome::Failed {
+                        status: Some(status),
+                        message: error.to_string(),
+                    }),
+                    None,
+                ),
+            },
+            Err(error) => {
+                let recorded = error.status.map(|status| {
+                    let mut recorded = ManifestEntry::new(&job.url, &job.path);
+                    recorded.status = Some(status);
+                    recorded.fetched_at = fetched_at;
+                    recorded
+                });
+                (
+                    report(Outcome::Failed {
+                        status: error.status,
+                        message: error.message,
+                    }),
+                    recorded,
+                )
+            }
+        }
+    }
+}
+
+fn is_retryable(status: u16) -> bool {
+    status == 408 || status == 429 || status >= 500
+}
+
+fn write_atomically(filepath: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = filepath.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut partial = filepath.as_os_str().to_owned();
+    partial.push(".part");
+    fs::write(&partial, content)?;
+    fs::rename(&partial, filepath)
+}
+
+pub fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (hour, minute, second) = (seconds % 86400 / 3600, seconds % 3600 / 60, seconds % 60);
+
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    const JURASSIC: &str = "<html><body><p>Jurassic</p></body></html>";
+
+    type Requests = Arc<Mutex<Vec<(String, String)>>>;
+
+    fn serve() -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+
+        thread::spawn(move || {
+            let mut counts = HashMap::new();
+
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let mut user_agent = String::new();
+
+                loop {
+                    let mut line = String::new();
+
+                    if reader.read_line(&mut line).unwrap() == 0 || line.trim().is_empty() {
+                        break;
+                    }
+
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("user-agent") {
+                            user_agent = value.trim().to_string();
+                        }
+                    }
+                }
+
+                let count = counts.entry(path.clone()).or_insert(0);
+                *count += 1;
+                seen.lock().unwrap().push((path.clone(), user_agent));
+
+                let (status, body) = match (path.as_str(), *count) {
+                    ("/wiki/Jurassic", _) => ("200 OK", JURASSIC),
+                    ("/flaky", 1) => ("503 Service Unavailable", ""),
+                    ("/flaky", _) => ("200 OK", "<html>flaky</html>"),
+                    _ => ("404 Not Found", ""),
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (address, requests)
+    }
+
+    #[test]
+    fn test_fetch_dataset() {
+        let (address, requests) = serve();
+        let temp = tempfile::tempdir().unwrap();
+        let directory = temp.path().join("fetch");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("existing.html"), "<html></html>").unwrap();
+        fs::write(
+            directory.join("dataset.csv"),
+            format!(
+                "url, type\n\
+                 {address}/wiki/Jurassic, article\n\
+                 {address}/flaky, article\n\
+                 {address}/gone, article\n\
+                 {address}/existing, article\n"
+            ),
+        )
+        .unwrap();
+
+        let options = Options {
+            concurrency: 2,
+            retries: 2,
+            backoff: Duration::from_millis(10),
+            interval: Duration::ZERO,
+            ..Options::recommended()
+        };
+        let fetcher = Fetcher::new(options.clone());
+        let reports = fetcher.fetch_dataset(&directory).unwrap();
+        let outcomes = reports
+            .iter()
+            .map(|report| report.outcome.clone())
+            .collect::<Vec<Outcome>>();
+
+        assert_eq!(
+            outcomes[..2],
+            [
+                Outcome::Fetched {
+                    status: 200,
+                    size: JURASSIC.len()
+                },
+                Outcome::Fetched {
+                    status: 200,
+                    size: 18
+                }
+            ]
+        );
+        assert!(matches!(
+            outcomes[2],
+            Outcome::Failed {
+                status: Some(404),
+                ..
+            }
+        ));
+        assert_eq!(outcomes[3], Outcome::Skipped);
+        assert_eq!(
+            fs::read_to_string(directory.join("wiki/Jurassic.html")).unwrap(),
+            JURASSIC
+        );
+
+        let manifest = Manifest::load(&directory).unwrap().unwrap();
+        let jurassic = manifest.get(&format!("{address}/wiki/Jurassic")).unwrap();
+        assert_eq!(jurassic.path, "wiki/Jurassic.html");
+        assert_eq!(jurassic.status, Some(200));
+        assert!(jurassic.sha256.is_some() && jurassic.fetched_at.is_some());
+        assert_eq!(
+            manifest.get(&format!("{address}/gone")).unwrap().status,
+            Some(404)
+        );
+
+        let resumed = fetcher.fetch_dataset(&directory).unwrap();
+        assert_eq!(resumed[0].outcome, Outcome::Skipped);
+        assert_eq!(resumed[1].outcome, Outcome::Skipped);
+
+        let requests = requests.lock().unwrap();
+        let paths = requests
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths.iter().filter(|path| **path == "/flaky").count(), 2);
+        assert_eq!(paths.iter().filter(|path| **path == "/gone").count(), 2);
+        assert!(!paths.contains(&"/existing"));
+        assert!(requests
+            .iter()
+            .all(|(_, user_agent)| *user_agent == options.user_agent));
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(1755338400)),
+            "2025-08-16T10:00:00Z"
+        );
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(951868799)),
+            "2000-02-29T23:59:59Z"
+        );
+    }
+}
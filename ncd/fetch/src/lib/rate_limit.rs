@@ -0,0 +1,47 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+
+        thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let start = Instant::now();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| limiter.wait());
+            }
+        });
+
+        assert!(start.elapsed() >= Duration::from_millis(60));
+    }
+}
@@ -0,0 +1,123 @@
+use benchmark::benchmarks::get_dataset_path;
+use fetch::{Fetcher, Options, Outcome};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "Usage:
+    fetch [OPTIONS] DATASET...     Download every URL of dataset.csv that has no file yet
+
+Options:
+    --concurrency N                Parallel requests (default 4)
+    --retries N                    Retries after timeouts, 408, 429 and 5xx (default 3)
+    --interval MS                  Minimum time between two requests (default 250)
+    --timeout S                    Timeout per request (default 30)
+    --user-agent AGENT             User-Agent header
+    --force                        Refetch pages that already have a file
+
+DATASET is a dataset name or a directory containing dataset.csv";
+
+fn parse_options(args: &[String]) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options::recommended();
+    let mut datasets = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {name}"))
+                .and_then(|value| {
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("Invalid value for {name}: {value}"))
+                })
+        };
+
+        match arg.as_str() {
+            "--concurrency" => options.concurrency = value(arg)? as usize,
+            "--retries" => options.retries = value(arg)? as u32,
+            "--interval" => options.interval = Duration::from_millis(value(arg)?),
+            "--timeout" => options.timeout = Duration::from_secs(value(arg)?),
+            "--user-agent" => {
+                options.user_agent = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {arg}"))?
+                    .clone()
+            }
+            "--force" => options.force = true,
+            option if option.starts_with("--") => return Err(format!("Unknown option: {option}")),
+            dataset => datasets.push(dataset.to_string()),
+        }
+    }
+
+    if datasets.is_empty() {
+        return Err("No dataset given".to_string());
+    }
+
+    Ok((options, datasets))
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    if args
+        .iter()
+        .any(|arg| matches!(arg.as_str(), "help" | "--help" | "-h"))
+    {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let (options, datasets) = match parse_options(&args) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{error}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let fetcher = Fetcher::new(options);
+    let mut failed = false;
+
+    for name in &datasets {
+        let directory = if PathBuf::from(name).is_dir() {
+            PathBuf::from(name)
+        } else {
+            get_dataset_path(name)
+        };
+
+        let reports = match fetcher.fetch_dataset(&directory) {
+            Ok(reports) => reports,
+            Err(error) => {
+                eprintln!("{name}: {error}");
+                failed = true;
+                continue;
+            }
+        };
+        let (mut fetched, mut skipped, mut errors) = (0, 0, 0);
+
+        for report in &reports {
+            match &report.outcome {
+                Outcome::Fetched { status, size } => {
+                    fetched += 1;
+                    println!("  {status} {} ({size} bytes)", report.url);
+                }
+                Outcome::Skipped => skipped += 1,
+                Outcome::Failed { status, message } => {
+                    errors += 1;
+                    let status = status.map_or("---".to_string(), |status| status.to_string());
+                    eprintln!("  {status} {}: {message}", report.url);
+                }
+            }
+        }
+
+        println!("{name}: {fetched} fetched, {skipped} skipped, {errors} failed");
+        failed |= errors > 0;
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+const UNKNOWN: u32 = 0;
+const SURROGATES: u32 = 0x800;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Varint,
+    Codepoint,
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Varint => "varint",
+            Self::Codepoint => "codepoint",
+        }
+    }
+
+    fn push(&self, symbol: u32, out: &mut Vec<u8>) {
+        match self {
+            Self::Varint => {
+                let mut value = symbol;
+                while value >= 0x80 {
+                    out.push((value & 0x7f) as u8 | 0x80);
+                    value >>= 7;
+                }
+                out.push(value as u8);
+            }
+            Self::Codepoint => {
+                let value = if symbol < 0xd800 {
+                    symbol
+                } else {
+                    symbol + SURROGATES
+                };
+                let character = char::from_u32(value).expect("Alphabet exceeds the Unicode range");
+                out.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+        }
+    }
+}
+
+// Splits a skeleton into tags and the text between them.
+pub fn tokens(skeleton: &str) -> impl Iterator<Item = &str> {
+    skeleton
+        .split_inclusive('>')
+        .flat_map(|chunk| match chunk.find('<') {
+            Some(start) if start > 0 => [Some(&chunk[..start]), Some(&chunk[start..])],
+            _ => [Some(chunk), None],
+        })
+        .flatten()
+}
+
+#[derive(Debug, Clone)]
+pub struct Alphabet {
+    encoding: Encoding,
+    symbols: HashMap<String, u32>,
+}
+
+impl Alphabet {
+    // The most frequent tokens get the smallest symbols, so they encode to a single byte.
+    pub fn new(encoding: Encoding, skeletons: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let mut counts = HashMap::<String, usize>::new();
+
+        for skeleton in skeletons {
+            for token in tokens(skeleton.as_ref()) {
+                match counts.get_mut(token) {
+                    Some(count) => *count += 1,
+                    None => {
+                        counts.insert(token.to_string(), 1);
+                    }
+                }
+            }
+        }
+
+        let mut ranked = counts.into_iter().collect::<Vec<(String, usize)>>();
+        ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let symbols = ranked
+            .into_iter()
+            .enumerate()
+            .map(|(index, (token, _))| (token, index as u32 + 1))
+            .collect();
+
+        Self { encoding, symbols }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn symbol(&self, token: &str) -> u32 {
+        self.symbols.get(token).copied().unwrap_or(UNKNOWN)
+    }
+
+    pub fn encode(&self, skeleton: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        for token in tokens(skeleton) {
+            self.encoding.push(self.symbol(token), &mut out);
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let skeleton =
+            r#"<html><body><div class="a"></div><div class="a"></div>text<p /></body></html>"#;
+        assert_eq!(
+            tokens(skeleton).collect::<Vec<&str>>(),
+            [
+                "<html>",
+                "<body>",
+                "<div class=\"a\">",
+                "</div>",
+                "<div class=\"a\">",
+                "</div>",
+                "text",
+                "<p />",
+                "</body>",
+                "</html>"
+            ]
+        );
+
+        let alphabet = Alphabet::new(Encoding::Varint, [skeleton]);
+        assert_eq!(alphabet.len(), 8);
+        assert_eq!(alphabet.symbol("</div>"), 1);
+        assert_eq!(alphabet.symbol("<div class=\"a\">"), 2);
+        assert_eq!(alphabet.symbol("<span>"), UNKNOWN);
+        assert_eq!(alphabet.encode(skeleton).len(), 10);
+        assert_eq!(alphabet.encode("<div class=\"a\"></div><span>"), [2, 1, 0]);
+
+        let mut out = Vec::new();
+        Encoding::Varint.push(300, &mut out);
+        assert_eq!(out, [0xac, 0x02]);
+
+        let codepoints = Alphabet::new(Encoding::Codepoint, [skeleton]);
+        assert!(std::str::from_utf8(&codepoints.encode(skeleton)).is_ok());
+        let mut out = Vec::new();
+        Encoding::Codepoint.push(0xd800, &mut out);
+        assert_eq!(std::str::from_utf8(&out).unwrap(), "\u{e000}");
+    }
+}
@@ -1,9 +1,48 @@
 use crate::compress::Compressor;
 
-use std::io::{BufWriter, Write};
+use brotli::enc::writer::CompressorWriterCustomAlloc;
+use brotli::enc::StandardAlloc;
+use brotli::{SliceWrapper, SliceWrapperMut};
+use std::cell::Cell;
+use std::io::{self, Read, Write};
 
 const QUALITY: u32 = 5;
 const LG_WINDOW_SIZE: u32 = 21;
+const BUFFER_SIZE: usize = 4096;
+
+thread_local! {
+    static BUFFER: Cell<Vec<u8>> = Cell::new(vec![0; BUFFER_SIZE]);
+}
+
+struct Buffer<'a>(&'a mut [u8]);
+
+impl SliceWrapper<u8> for Buffer<'_> {
+    fn slice(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl SliceWrapperMut<u8> for Buffer<'_> {
+    fn slice_mut(&mut self) -> &mut [u8] {
+        self.0
+    }
+}
+
+#[derive(Default)]
+struct Counter {
+    bytes: usize,
+}
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
 pub struct CompressBrotli {
     quality: u32,
@@ -24,24 +63,59 @@ impl CompressBrotli {
             lg_window_size: LG_WINDOW_SIZE,
         }
     }
+
+    pub fn quality(&self) -> u32 {
+        self.quality
+    }
+
+    pub fn lg_window_size(&self) -> u32 {
+        self.lg_window_size
+    }
+
+    // The encoder state (hash tables sized by quality and window) is still allocated for
+    // every measurement. The brotli crate has no way to reset an encoder: nothing returns a
+    // finished `BrotliEncoderStateStruct` to its initial state, the writer can only be consumed
+    // by `into_inner`, and `BrotliEncoderDestroyInstance` frees the tables instead of clearing
+    // them. A reused state would also keep the previous input in its window and hash tables,
+    // changing the measured sizes, so only the output buffer is reused.
+    // The buffer is taken out of the thread local rather than borrowed, so a nested call from
+    // inside `write` allocates its own buffer instead of panicking.
+    fn measure(&self, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<usize> {
+        let mut buffer = BUFFER.take();
+        buffer.resize(BUFFER_SIZE, 0);
+
+        let mut writer = CompressorWriterCustomAlloc::new(
+            Counter::default(),
+            Buffer(&mut buffer),
+            StandardAlloc::default(),
+            self.quality,
+            self.lg_window_size,
+        );
+        let result = write(&mut writer);
+        let bytes = writer.into_inner().bytes;
+
+        BUFFER.set(buffer);
+        result.map(|()| bytes)
+    }
 }
 
 impl Compressor for CompressBrotli {
-    fn get_compressed_size(&self, buf: &str) -> usize {
-        let mut out = BufWriter::new(Vec::new());
-        let buffer_size = buf.len();
+    fn get_compressed_size(&self, buf: &[u8]) -> usize {
+        self.get_compressed_size_chunks(&[buf])
+    }
 
-        {
-            let mut writer = brotli::CompressorWriter::new(
-                &mut out,
-                buffer_size,
-                self.quality,
-                self.lg_window_size,
-            );
-            writer.write_all(buf.as_bytes()).unwrap();
-        }
+    fn get_compressed_size_chunks(&self, chunks: &[&[u8]]) -> usize {
+        self.measure(|writer| {
+            for chunk in chunks {
+                writer.write_all(chunk)?;
+            }
+            Ok(())
+        })
+        .unwrap()
+    }
 
-        out.into_inner().unwrap().len()
+    fn get_compressed_size_reader(&self, reader: &mut dyn Read) -> io::Result<usize> {
+        self.measure(|writer| io::copy(reader, writer).map(|_| ()))
     }
 }
 
@@ -50,19 +124,63 @@ mod tests {
     use super::*;
     use assert_approx_eq::assert_approx_eq;
 
-    fn read_from_file(file_path: &str) -> String {
-        let project_root = env!("CARGO_MANIFEST_DIR");
-        let full_path = std::path::Path::new(project_root).join(file_path);
-        dbg!(&full_path);
-        std::fs::read_to_string(full_path).expect("Failed to read file")
+    fn top_rated_page() -> String {
+        let items = (0..250u64)
+            .map(|rank| {
+                let id = rank.wrapping_mul(2654435761) % 10_000_000;
+                format!(
+                    "<li class=\"item\"><a href=\"/title/tt{id:07}/\">{rank}. Title {id:x}</a><span class=\"rating\">{}.{}</span></li>",
+                    rank % 10,
+                    id % 10
+                )
+            })
+            .collect::<String>();
+
+        format!("<html><head><title>Top rated movies</title></head><body><ul class=\"list\">{items}</ul></body></html>")
     }
 
     #[test]
     fn test_compress_brotli() {
         let compressor = CompressBrotli::recommended();
-        let page_html =
-            read_from_file("../../../dataset/imdb/list/ls541382956/?ref_=tt_urls_2.html");
-        let result = compressor.get_distance(&page_html, &page_html);
+        let page_html = top_rated_page();
+        let result = compressor.get_distance(page_html.as_bytes(), page_html.as_bytes());
         assert_approx_eq!(result, 0.0, 0.01);
     }
+
+    #[test]
+    fn test_chunks_and_reader() {
+        let compressor = CompressBrotli::recommended();
+        let page_html = top_rated_page();
+        let (head, tail) = page_html.as_bytes().split_at(page_html.len() / 3);
+        let whole = compressor.get_compressed_size(page_html.as_bytes());
+
+        assert_eq!(compressor.get_compressed_size_chunks(&[head, tail]), whole);
+        let streamed = compressor
+            .get_compressed_size_reader(&mut page_html.as_bytes())
+            .unwrap();
+        assert_eq!(streamed, whole);
+
+        let mut out = Vec::new();
+        {
+            let mut writer =
+                brotli::CompressorWriter::new(&mut out, BUFFER_SIZE, QUALITY, LG_WINDOW_SIZE);
+            writer.write_all(page_html.as_bytes()).unwrap();
+        }
+        assert_eq!(out.len(), whole);
+
+        struct Nested<'a>(&'a CompressBrotli, &'a [u8]);
+        impl Read for Nested<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                assert_eq!(
+                    self.0.get_compressed_size(self.1),
+                    self.0.get_compressed_size(self.1)
+                );
+                self.1.read(buf)
+            }
+        }
+        let nested = compressor
+            .get_compressed_size_reader(&mut Nested(&compressor, page_html.as_bytes()))
+            .unwrap();
+        assert_eq!(nested, whole);
+    }
 }
@@ -0,0 +1,182 @@
+use crate::compress::Compressor;
+use std::collections::HashMap;
+
+const LITERAL_BITS: f64 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Lz76,
+    Lz78,
+}
+
+pub struct CompressLz {
+    variant: Variant,
+}
+
+impl CompressLz {
+    pub fn new(variant: Variant) -> Self {
+        Self { variant }
+    }
+
+    pub fn recommended() -> Self {
+        Self::new(Variant::Lz76)
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn phrases(&self, buf: &[u8]) -> usize {
+        match self.variant {
+            Variant::Lz76 => lz76_phrases(buf),
+            Variant::Lz78 => lz78_phrases(buf),
+        }
+    }
+}
+
+impl Compressor for CompressLz {
+    // Size in bits: every phrase costs a reference to an earlier phrase plus one literal.
+    fn get_compressed_size(&self, buf: &[u8]) -> usize {
+        let phrases = self.phrases(buf) as f64;
+
+        if phrases == 0.0 {
+            return 0;
+        }
+
+        (phrases * (phrases.log2() + LITERAL_BITS)).ceil() as usize
+    }
+}
+
+fn lz78_phrases(buf: &[u8]) -> usize {
+    let mut trie = HashMap::<(usize, u8), usize>::new();
+    let mut phrases = 0;
+    let mut node = 0;
+
+    for &byte in buf {
+        match trie.get(&(node, byte)) {
+            Some(&child) => node = child,
+            None => {
+                phrases += 1;
+                trie.insert((node, byte), phrases);
+                node = 0;
+            }
+        }
+    }
+
+    if node != 0 {
+        phrases += 1;
+    }
+
+    phrases
+}
+
+fn suffix_array(buf: &[u8]) -> Vec<usize> {
+    let n = buf.len();
+    let mut suffixes = (0..n).collect::<Vec<usize>>();
+    let mut rank = buf
+        .iter()
+        .map(|&byte| byte as usize)
+        .collect::<Vec<usize>>();
+    let mut next = vec![0; n];
+    let mut length = 1;
+
+    loop {
+        let key = |i: usize| (rank[i], rank.get(i + length).map_or(0, |&rank| rank + 1));
+        suffixes.sort_unstable_by_key(|&i| key(i));
+
+        next[suffixes[0]] = 0;
+        for window in 1..n {
+            let (previous, current) = (suffixes[window - 1], suffixes[window]);
+            next[current] = next[previous] + usize::from(key(previous) != key(current));
+        }
+
+        std::mem::swap(&mut rank, &mut next);
+
+        if rank[suffixes[n - 1]] == n - 1 || length >= n {
+            return suffixes;
+        }
+
+        length *= 2;
+    }
+}
+
+fn lz76_phrases(buf: &[u8]) -> usize {
+    let n = buf.len();
+
+    if n == 0 {
+        return 0;
+    }
+
+    let suffixes = suffix_array(buf);
+    let mut previous = vec![None; n];
+    let mut following = vec![None; n];
+    let mut stack = Vec::<usize>::new();
+
+    for &position in &suffixes {
+        while stack.last().is_some_and(|&top| top > position) {
+            stack.pop();
+        }
+        previous[position] = stack.last().copied();
+        stack.push(position);
+    }
+
+    stack.clear();
+
+    for &position in suffixes.iter().rev() {
+        while stack.last().is_some_and(|&top| top > position) {
+            stack.pop();
+        }
+        following[position] = stack.last().copied();
+        stack.push(position);
+    }
+
+    let common_prefix = |a: usize, b: usize| {
+        buf[a..]
+            .iter()
+            .zip(&buf[b..])
+            .take_while(|(x, y)| x == y)
+            .count()
+    };
+    let mut phrases = 0;
+    let mut position = 0;
+
+    while position < n {
+        let longest = [previous[position], following[position]]
+            .into_iter()
+            .flatten()
+            .map(|earlier| common_prefix(position, earlier))
+            .max()
+            .unwrap_or(0);
+        phrases += 1;
+        position += longest + 1;
+    }
+
+    phrases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phrases() {
+        let lz76 = CompressLz::new(Variant::Lz76);
+        let lz78 = CompressLz::new(Variant::Lz78);
+
+        // Kaspar and Schuster: 0 · 001 · 10 · 100 · 1000 · 101
+        assert_eq!(lz76.phrases(b"0001101001000101"), 6);
+        // 0 · 00 · 1 · 10 · 100 · 1000 · 101
+        assert_eq!(lz78.phrases(b"0001101001000101"), 7);
+        assert_eq!(lz76.phrases(b"aaaaaaaa"), 2);
+        assert_eq!(lz76.phrases(b""), 0);
+        assert_eq!(lz78.phrases(b"ab"), 2);
+
+        let page = "<div><p></p></div>".repeat(100).into_bytes();
+        assert!(lz76.get_distance(&page, &page) < 0.05);
+        assert!(lz76.get_compressed_size(&page) < lz78.get_compressed_size(&page));
+        assert_eq!(
+            lz78.get_compressed_size_chunks(&[&page[..10], &page[10..]]),
+            lz78.get_compressed_size(&page)
+        );
+    }
+}
@@ -1,30 +1,41 @@
 pub mod brotli;
+pub mod lz;
+pub mod ppm;
 
+use crate::distance::{CompressedSizes, Distance, DistanceReport};
 use std::cmp;
+use std::io::{self, Read};
 
 pub trait Compressor {
-    fn get_distance(&self, page_a: &str, page_b: &str) -> f64 {
-        let length_combined = self.get_combined_length(page_a, page_b);
-        let a_compressed = self.get_compressed_size(page_a);
-        let b_compressed = self.get_compressed_size(page_b);
-
-        let min = cmp::min(a_compressed, b_compressed);
-        let max = cmp::max(a_compressed, b_compressed);
+    fn get_distance(&self, page_a: &[u8], page_b: &[u8]) -> f64 {
+        Distance::Ncd.compute(&self.get_sizes(page_a, page_b))
+    }
 
-        if length_combined < min {
-            return 0.0;
-        }
+    fn get_distance_report(&self, page_a: &[u8], page_b: &[u8]) -> DistanceReport {
+        Distance::Ncd.report(self, page_a, page_b)
+    }
 
-        (length_combined - min) as f64 / max as f64
+    fn get_sizes(&self, page_a: &[u8], page_b: &[u8]) -> CompressedSizes {
+        CompressedSizes::new(self, page_a, page_b)
     }
 
-    fn get_compressed_size(&self, buf: &str) -> usize;
+    fn get_compressed_size(&self, buf: &[u8]) -> usize;
 
-    fn get_combined_length(&self, page_a: &str, page_b: &str) -> usize {
-        let page_ab = page_a.to_owned() + page_b;
-        let length_combined_a_b = self.get_compressed_size(&page_ab);
+    // Compressed size of the chunks as one input; streaming backends override this
+    // to avoid building the concatenation.
+    fn get_compressed_size_chunks(&self, chunks: &[&[u8]]) -> usize {
+        self.get_compressed_size(&chunks.concat())
+    }
+
+    fn get_compressed_size_reader(&self, reader: &mut dyn Read) -> io::Result<usize> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        Ok(self.get_compressed_size(&buf))
+    }
 
-        let length_combined_b_a = self.get_compressed_size(&(page_b.to_owned() + page_a));
+    fn get_combined_length(&self, page_a: &[u8], page_b: &[u8]) -> usize {
+        let length_combined_a_b = self.get_compressed_size_chunks(&[page_a, page_b]);
+        let length_combined_b_a = self.get_compressed_size_chunks(&[page_b, page_a]);
         cmp::min(length_combined_a_b, length_combined_b_a)
     }
 }
@@ -0,0 +1,120 @@
+use crate::compress::Compressor;
+use std::collections::HashMap;
+
+const ORDER: usize = 4;
+const ALPHABET: f64 = 256.0;
+
+pub struct CompressPpm {
+    order: usize,
+}
+
+impl CompressPpm {
+    pub fn new(order: usize) -> Self {
+        Self { order }
+    }
+
+    pub fn recommended() -> Self {
+        Self::new(ORDER)
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    // Code length in bits of an adaptive PPM-C model with symbol exclusion.
+    pub fn code_length(&self, buf: &[u8]) -> f64 {
+        let mut contexts = HashMap::<&[u8], Vec<(u8, u32)>>::new();
+        let mut bits = 0.0;
+
+        for (position, &symbol) in buf.iter().enumerate() {
+            let mut excluded = [false; 256];
+            let mut coded = false;
+
+            for order in (0..=self.order.min(position)).rev() {
+                let Some(counts) = contexts.get(&buf[position - order..position]) else {
+                    continue;
+                };
+
+                let (mut total, mut distinct, mut found) = (0, 0, None);
+                for &(seen, count) in counts {
+                    if excluded[seen as usize] {
+                        continue;
+                    }
+                    total += count;
+                    distinct += 1;
+                    if seen == symbol {
+                        found = Some(count);
+                    }
+                }
+
+                if distinct == 0 {
+                    continue;
+                }
+
+                let denominator = (total + distinct) as f64;
+                if let Some(count) = found {
+                    bits -= (count as f64 / denominator).log2();
+                    coded = true;
+                    break;
+                }
+
+                bits -= (distinct as f64 / denominator).log2();
+                for &(seen, _) in counts {
+                    excluded[seen as usize] = true;
+                }
+            }
+
+            if !coded {
+                let remaining = excluded.iter().filter(|&&excluded| !excluded).count();
+                bits += (remaining.max(1) as f64).log2().min(ALPHABET.log2());
+            }
+
+            for order in 0..=self.order.min(position) {
+                let counts = contexts
+                    .entry(&buf[position - order..position])
+                    .or_default();
+                match counts.iter_mut().find(|(seen, _)| *seen == symbol) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((symbol, 1)),
+                }
+            }
+        }
+
+        bits
+    }
+}
+
+impl Compressor for CompressPpm {
+    fn get_compressed_size(&self, buf: &[u8]) -> usize {
+        self.code_length(buf).ceil() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_length() {
+        let ppm = CompressPpm::recommended();
+        assert_eq!(ppm.code_length(b""), 0.0);
+        assert_eq!(ppm.code_length(b"a"), 8.0);
+
+        let list = |tag: &str| {
+            (0..200u64)
+                .map(|index| {
+                    let id = index.wrapping_mul(2654435761) % 100_000;
+                    format!("<{tag} class=\"item\"><a href=\"/item/{id}\">{index}</a></{tag}>")
+                })
+                .collect::<String>()
+                .into_bytes()
+        };
+        let (page, other) = (list("li"), list("td"));
+        let article = "<article><h1>Title</h1><p>Some text in a paragraph.</p></article>"
+            .repeat(50)
+            .into_bytes();
+        assert!(ppm.get_distance(&page, &page) < ppm.get_distance(&page, &other));
+        assert!(ppm.get_distance(&page, &other) < ppm.get_distance(&page, &article));
+        assert!(ppm.get_compressed_size(&page) < CompressPpm::new(0).get_compressed_size(&page));
+    }
+}
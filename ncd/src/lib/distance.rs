@@ -0,0 +1,240 @@
+use crate::compress::Compressor;
+use std::cmp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedSizes {
+    pub a: usize,
+    pub b: usize,
+    pub ab: usize,
+    pub ba: usize,
+}
+
+impl CompressedSizes {
+    pub fn new<C: Compressor + ?Sized>(compressor: &C, page_a: &[u8], page_b: &[u8]) -> Self {
+        Self {
+            a: compressor.get_compressed_size(page_a),
+            b: compressor.get_compressed_size(page_b),
+            ab: compressor.get_compressed_size_chunks(&[page_a, page_b]),
+            ba: compressor.get_compressed_size_chunks(&[page_b, page_a]),
+        }
+    }
+
+    pub fn combined(&self) -> usize {
+        cmp::min(self.ab, self.ba)
+    }
+
+    pub fn min(&self) -> usize {
+        cmp::min(self.a, self.b)
+    }
+
+    pub fn max(&self) -> usize {
+        cmp::max(self.a, self.b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Distance {
+    Ncd,
+    NcmMax,
+    NcmSum,
+    Cdm,
+    Clm,
+}
+
+impl Distance {
+    pub fn all() -> Vec<Self> {
+        vec![Self::Ncd, Self::NcmMax, Self::NcmSum, Self::Cdm, Self::Clm]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ncd => "ncd",
+            Self::NcmMax => "ncm_max",
+            Self::NcmSum => "ncm_sum",
+            Self::Cdm => "cdm",
+            Self::Clm => "clm",
+        }
+    }
+
+    fn evaluate(&self, sizes: &CompressedSizes) -> (f64, Branch) {
+        let (a, b, ab, ba) = (
+            sizes.a as f64,
+            sizes.b as f64,
+            sizes.ab as f64,
+            sizes.ba as f64,
+        );
+        let combined = sizes.combined() as f64;
+
+        if sizes.max() == 0 || combined == 0.0 {
+            return (0.0, Branch::Empty);
+        }
+
+        let distance = match self {
+            Self::Ncd => (combined - sizes.min() as f64) / sizes.max() as f64,
+            Self::NcmMax => (ab - a).max(ba - b) / sizes.max() as f64,
+            Self::NcmSum => ((ab - a) + (ba - b)) / combined,
+            Self::Cdm => combined / (a + b),
+            Self::Clm => 1.0 - (a + b - combined) / combined,
+        };
+
+        if distance < 0.0 {
+            (distance, Branch::Clamped)
+        } else {
+            (distance, Branch::Computed)
+        }
+    }
+
+    pub fn compute(&self, sizes: &CompressedSizes) -> f64 {
+        self.evaluate(sizes).0.max(0.0)
+    }
+
+    pub fn between<C: Compressor + ?Sized>(
+        &self,
+        compressor: &C,
+        page_a: &[u8],
+        page_b: &[u8],
+    ) -> f64 {
+        self.compute(&CompressedSizes::new(compressor, page_a, page_b))
+    }
+
+    pub fn report<C: Compressor + ?Sized>(
+        &self,
+        compressor: &C,
+        page_a: &[u8],
+        page_b: &[u8],
+    ) -> DistanceReport {
+        DistanceReport::new(
+            *self,
+            CompressedSizes::new(compressor, page_a, page_b),
+            page_a.len(),
+            page_b.len(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    Computed,
+    Clamped,
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceReport {
+    pub distance: Distance,
+    pub sizes: CompressedSizes,
+    pub length_a: usize,
+    pub length_b: usize,
+    pub unclamped: f64,
+    pub value: f64,
+    pub branch: Branch,
+}
+
+impl DistanceReport {
+    pub fn new(
+        distance: Distance,
+        sizes: CompressedSizes,
+        length_a: usize,
+        length_b: usize,
+    ) -> Self {
+        let (unclamped, branch) = distance.evaluate(&sizes);
+
+        Self {
+            distance,
+            sizes,
+            length_a,
+            length_b,
+            unclamped,
+            value: unclamped.max(0.0),
+            branch,
+        }
+    }
+
+    pub fn exceeds_one(&self) -> bool {
+        self.value > 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_distances() {
+        let sizes = CompressedSizes {
+            a: 100,
+            b: 200,
+            ab: 260,
+            ba: 250,
+        };
+
+        assert_approx_eq!(Distance::Ncd.compute(&sizes), 150.0 / 200.0);
+        assert_approx_eq!(Distance::NcmMax.compute(&sizes), 160.0 / 200.0);
+        assert_approx_eq!(Distance::NcmSum.compute(&sizes), 210.0 / 250.0);
+        assert_approx_eq!(Distance::Cdm.compute(&sizes), 250.0 / 300.0);
+        assert_approx_eq!(Distance::Clm.compute(&sizes), 1.0 - 50.0 / 250.0);
+
+        let identical = CompressedSizes {
+            a: 100,
+            b: 100,
+            ab: 95,
+            ba: 95,
+        };
+        assert_eq!(Distance::Ncd.compute(&identical), 0.0);
+        assert_eq!(Distance::NcmMax.compute(&identical), 0.0);
+    }
+
+    #[test]
+    fn test_distance_report() {
+        let clamped = DistanceReport::new(
+            Distance::Ncd,
+            CompressedSizes {
+                a: 100,
+                b: 100,
+                ab: 95,
+                ba: 97,
+            },
+            400,
+            400,
+        );
+        assert_eq!(clamped.branch, Branch::Clamped);
+        assert_approx_eq!(clamped.unclamped, -0.05);
+        assert_eq!(clamped.value, 0.0);
+
+        let above_one = DistanceReport::new(
+            Distance::Ncd,
+            CompressedSizes {
+                a: 10,
+                b: 20,
+                ab: 32,
+                ba: 33,
+            },
+            12,
+            25,
+        );
+        assert_eq!(above_one.branch, Branch::Computed);
+        assert_approx_eq!(above_one.value, 1.1);
+        assert!(above_one.exceeds_one());
+
+        let empty = DistanceReport::new(
+            Distance::Cdm,
+            CompressedSizes {
+                a: 0,
+                b: 0,
+                ab: 0,
+                ba: 0,
+            },
+            0,
+            0,
+        );
+        assert_eq!(empty.branch, Branch::Empty);
+
+        let compressor = crate::compress::brotli::CompressBrotli::recommended();
+        let (page_a, page_b) = (b"<p></p>".as_slice(), b"<div></div>".as_slice());
+        let report = compressor.get_distance_report(page_a, page_b);
+        assert_eq!((report.length_a, report.length_b), (7, 11));
+        assert_eq!(report.sizes, compressor.get_sizes(page_a, page_b));
+        assert_eq!(report.value, compressor.get_distance(page_a, page_b));
+    }
+}
@@ -1,15 +1,24 @@
+pub mod alphabet;
 pub mod compress;
-mod strip;
+pub mod distance;
+pub mod lsh;
+pub mod matrix;
+pub mod rng;
+pub mod strip;
 
 use crate::compress::Compressor;
 use strip::StripHtml;
 
-pub fn calculate(page_a: &str, page_b: &str) -> f64 {
+pub fn skeleton(page: &str) -> String {
     let stripper = strip::filter_attributes::FilterAttributes {};
-    let stripped_a = stripper.strip_html(page_a);
-    let stripped_b = stripper.strip_html(page_b);
+    stripper.strip_html(page)
+}
+
+pub fn calculate(page_a: &str, page_b: &str) -> f64 {
+    let stripped_a = skeleton(page_a);
+    let stripped_b = skeleton(page_b);
     let compressor = compress::brotli::CompressBrotli::recommended();
-    compressor.get_distance(&stripped_a, &stripped_b)
+    compressor.get_distance(stripped_a.as_bytes(), stripped_b.as_bytes())
 }
 
 #[cfg(test)]
@@ -0,0 +1,220 @@
+use crate::compress::Compressor;
+use crate::distance::Distance;
+use crate::rng::Rng;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+const NUM_HASHES: usize = 128;
+const SHINGLE_SIZE: usize = 4;
+const BANDS: usize = 32;
+const MERSENNE_PRIME: u64 = (1 << 61) - 1;
+
+pub struct MinHash {
+    shingle_size: usize,
+    coefficients: Vec<(u64, u64)>,
+}
+
+impl MinHash {
+    pub fn new(num_hashes: usize, shingle_size: usize) -> Self {
+        let mut rng = Rng::new(0);
+        let coefficients = (0..num_hashes)
+            .map(|_| {
+                let a = rng.next_u64() % (MERSENNE_PRIME - 1) + 1;
+                let b = rng.next_u64() % MERSENNE_PRIME;
+                (a, b)
+            })
+            .collect();
+
+        Self {
+            shingle_size,
+            coefficients,
+        }
+    }
+
+    pub fn recommended() -> Self {
+        Self::new(NUM_HASHES, SHINGLE_SIZE)
+    }
+
+    pub fn num_hashes(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    pub fn shingles(&self, skeleton: &str) -> HashSet<u64> {
+        let tokens = skeleton
+            .split_inclusive('>')
+            .filter(|token| !token.trim().is_empty())
+            .collect::<Vec<&str>>();
+
+        if tokens.len() <= self.shingle_size {
+            return HashSet::from([hash_tokens(&tokens)]);
+        }
+
+        tokens.windows(self.shingle_size).map(hash_tokens).collect()
+    }
+
+    pub fn signature(&self, skeleton: &str) -> Vec<u64> {
+        let shingles = self.shingles(skeleton);
+
+        self.coefficients
+            .iter()
+            .map(|&(a, b)| {
+                shingles
+                    .iter()
+                    .map(|&shingle| {
+                        let x = shingle % MERSENNE_PRIME;
+                        ((a as u128 * x as u128 + b as u128) % MERSENNE_PRIME as u128) as u64
+                    })
+                    .min()
+                    .unwrap_or(u64::MAX)
+            })
+            .collect()
+    }
+}
+
+pub fn estimate_similarity(signature_a: &[u64], signature_b: &[u64]) -> f64 {
+    if signature_a.is_empty() {
+        return 0.0;
+    }
+
+    let equal = signature_a
+        .iter()
+        .zip(signature_b)
+        .filter(|(a, b)| a == b)
+        .count();
+    equal as f64 / signature_a.len() as f64
+}
+
+pub struct Prefilter {
+    min_hash: MinHash,
+    bands: usize,
+}
+
+impl Prefilter {
+    pub fn new(min_hash: MinHash, bands: usize) -> Self {
+        assert!(
+            bands > 0 && min_hash.num_hashes().is_multiple_of(bands),
+            "Number of hashes {} is not divisible into {} bands",
+            min_hash.num_hashes(),
+            bands
+        );
+        Self { min_hash, bands }
+    }
+
+    pub fn recommended() -> Self {
+        Self::new(MinHash::recommended(), BANDS)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.min_hash.num_hashes() / self.bands
+    }
+
+    pub fn threshold(&self) -> f64 {
+        (1.0 / self.bands as f64).powf(1.0 / self.rows() as f64)
+    }
+
+    pub fn candidate_pairs(&self, skeletons: &[String]) -> BTreeSet<(usize, usize)> {
+        let rows = self.rows();
+        let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+
+        for (index, skeleton) in skeletons.iter().enumerate() {
+            let signature = self.min_hash.signature(skeleton);
+
+            for (band, chunk) in signature.chunks(rows).enumerate() {
+                let mut hasher = DefaultHasher::new();
+                chunk.hash(&mut hasher);
+                buckets
+                    .entry((band, hasher.finish()))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        let mut pairs = BTreeSet::new();
+
+        for members in buckets.values() {
+            for (i, &a) in members.iter().enumerate() {
+                for &b in &members[i + 1..] {
+                    pairs.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    // Exact distances of the candidate pairs only; every other pair is never compressed.
+    pub fn candidate_distances(
+        &self,
+        skeletons: &[String],
+        compressor: &(impl Compressor + ?Sized),
+        distance: Distance,
+    ) -> Vec<(usize, usize, f64)> {
+        self.candidate_pairs(skeletons)
+            .into_iter()
+            .map(|(a, b)| {
+                (
+                    a,
+                    b,
+                    distance.between(compressor, skeletons[a].as_bytes(), skeletons[b].as_bytes()),
+                )
+            })
+            .collect()
+    }
+}
+
+fn hash_tokens(tokens: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tokens.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skeleton;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_candidate_pairs() {
+        let list = |items: usize| {
+            let rows = "<li class=\"item\"><a href=\"#\">Item</a></li>".repeat(items);
+            format!(
+                "<html><body><div id=\"main\"><ul class=\"list\">{rows}</ul></div></body></html>"
+            )
+        };
+        let article = r#"<html><head><title>Title</title></head><body>
+            <article class="post"><h1>Heading</h1><p>Text</p><p>More</p>
+            <footer><span class="author">Author</span></footer></article></body></html>"#;
+        let skeletons = [list(20), list(21), article.to_string()]
+            .iter()
+            .map(|page| skeleton(page))
+            .collect::<Vec<String>>();
+
+        let pairs = Prefilter::recommended().candidate_pairs(&skeletons);
+        assert!(pairs.contains(&(0, 1)));
+        assert!(!pairs.contains(&(0, 2)));
+        assert!(!pairs.contains(&(1, 2)));
+
+        struct Counting(Mutex<Vec<usize>>);
+        impl Compressor for Counting {
+            fn get_compressed_size(&self, buf: &[u8]) -> usize {
+                self.0.lock().unwrap().push(buf.len());
+                buf.len()
+            }
+        }
+        let counting = Counting(Mutex::new(Vec::new()));
+        let distances =
+            Prefilter::recommended().candidate_distances(&skeletons, &counting, Distance::Ncd);
+        let compressed = pairs
+            .iter()
+            .flat_map(|&(a, b)| {
+                let (a, b) = (skeletons[a].len(), skeletons[b].len());
+                [a, b, a + b, a + b]
+            })
+            .collect::<Vec<usize>>();
+
+        assert_eq!(distances.len(), pairs.len());
+        assert_eq!(counting.0.into_inner().unwrap(), compressed);
+    }
+}
@@ -0,0 +1,307 @@
+use rayon::prelude::*;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+const MAGIC: &[u8; 4] = b"NCDM";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "distance matrix computation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+// Symmetric distances with a zero diagonal, stored as the lower triangle in row order so
+// that adding a document appends one row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CondensedMatrix {
+    size: usize,
+    values: Vec<f64>,
+}
+
+impl CondensedMatrix {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            values: vec![0.0; pairs(size)],
+        }
+    }
+
+    pub fn from_fn(size: usize, distance: impl Fn(usize, usize) -> f64 + Sync) -> Self {
+        let never = AtomicBool::new(false);
+        Self::compute(size, distance, |_| {}, &never).unwrap()
+    }
+
+    // Computes every pair once, in parallel over rows, calling `distance` with the smaller
+    // index first. The progress callback runs after each pair; setting `cancel` stops the
+    // remaining pairs.
+    pub fn compute(
+        size: usize,
+        distance: impl Fn(usize, usize) -> f64 + Sync,
+        progress: impl Fn(Progress) + Sync,
+        cancel: &AtomicBool,
+    ) -> Result<Self, Cancelled> {
+        let total = pairs(size);
+        let completed = AtomicUsize::new(0);
+
+        let rows = (0..size)
+            .into_par_iter()
+            .map(|a| {
+                (0..a)
+                    .map(|b| {
+                        if cancel.load(Ordering::Relaxed) {
+                            return Err(Cancelled);
+                        }
+
+                        let value = distance(b, a);
+                        progress(Progress {
+                            completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                            total,
+                        });
+                        Ok(value)
+                    })
+                    .collect::<Result<Vec<f64>, Cancelled>>()
+            })
+            .collect::<Result<Vec<Vec<f64>>, Cancelled>>()?;
+
+        Ok(Self {
+            size,
+            values: rows.concat(),
+        })
+    }
+
+    // Averages both triangles, so asymmetric distances become symmetric.
+    pub fn from_square(distances: &[Vec<f64>]) -> Self {
+        let size = distances.len();
+        let values = (0..size)
+            .flat_map(|a| (0..a).map(move |b| (distances[a][b] + distances[b][a]) / 2.0))
+            .collect();
+
+        Self { size, values }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    fn index(&self, a: usize, b: usize) -> usize {
+        let (a, b) = if a > b { (a, b) } else { (b, a) };
+        pairs(a) + b
+    }
+
+    pub fn get(&self, a: usize, b: usize) -> f64 {
+        if a == b {
+            0.0
+        } else {
+            self.values[self.index(a, b)]
+        }
+    }
+
+    pub fn set(&mut self, a: usize, b: usize, value: f64) {
+        assert_ne!(a, b, "The diagonal of a condensed matrix is always zero");
+        let index = self.index(a, b);
+        self.values[index] = value;
+    }
+
+    // Adds a document, computing only its distances to the existing ones.
+    pub fn push(&mut self, distance: impl Fn(usize) -> f64 + Sync) -> usize {
+        let row = (0..self.size)
+            .into_par_iter()
+            .map(&distance)
+            .collect::<Vec<f64>>();
+        self.values.extend(row);
+        self.size += 1;
+        self.size - 1
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        assert!(index < self.size, "Index {index} is out of bounds");
+        let kept = (0..self.size)
+            .filter(|&other| other != index)
+            .collect::<Vec<usize>>();
+        *self = self.select(&kept);
+    }
+
+    // The submatrix of the given documents, in the given order.
+    pub fn select(&self, indices: &[usize]) -> Self {
+        let values = (0..indices.len())
+            .flat_map(|a| (0..a).map(move |b| self.get(indices[a], indices[b])))
+            .collect();
+
+        Self {
+            size: indices.len(),
+            values,
+        }
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.size as u64).to_le_bytes())?;
+
+        for value in &self.values {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a condensed distance matrix",
+            ));
+        }
+
+        let mut word = [0; 8];
+        reader.read_exact(&mut word)?;
+        let size = u64::from_le_bytes(word);
+        let length = size
+            .checked_mul(size.saturating_sub(1))
+            .and_then(|product| (product / 2).checked_mul(8))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Matrix size {size} is too large"),
+                )
+            })?;
+
+        // The buffer grows with the data actually read, so a corrupt size cannot allocate more
+        // than the input holds.
+        let mut bytes = Vec::new();
+        reader.take(length).read_to_end(&mut bytes)?;
+
+        if bytes.len() as u64 != length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(Self {
+            size: size as usize,
+            values: bytes
+                .chunks_exact(8)
+                .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+                .collect(),
+        })
+    }
+
+    pub fn to_square(&self) -> Vec<Vec<f64>> {
+        (0..self.size)
+            .map(|a| (0..self.size).map(|b| self.get(a, b)).collect())
+            .collect()
+    }
+}
+
+fn pairs(size: usize) -> usize {
+    size * size.saturating_sub(1) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_condensed_matrix() {
+        let calls = Mutex::new(Vec::new());
+        let seen = Mutex::new(Vec::new());
+        let never = AtomicBool::new(false);
+        let matrix = CondensedMatrix::compute(
+            4,
+            |a, b| {
+                calls.lock().unwrap().push((a, b));
+                (a * 10 + b) as f64
+            },
+            |progress| seen.lock().unwrap().push(progress),
+            &never,
+        )
+        .unwrap();
+
+        assert_eq!(matrix.values(), [1.0, 2.0, 12.0, 3.0, 13.0, 23.0]);
+        assert_eq!(matrix.get(3, 1), 13.0);
+        assert_eq!(matrix.get(2, 2), 0.0);
+        assert_eq!(calls.lock().unwrap().len(), 6);
+        assert!(calls.lock().unwrap().iter().all(|(a, b)| a < b));
+
+        let mut completed = seen
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|progress| (progress.completed, progress.total))
+            .collect::<Vec<(usize, usize)>>();
+        completed.sort_unstable();
+        assert_eq!(completed, (1..=6).map(|i| (i, 6)).collect::<Vec<_>>());
+
+        let square = matrix.to_square();
+        assert_eq!(square[1][3], square[3][1]);
+        assert_eq!(CondensedMatrix::from_square(&square), matrix);
+        assert_eq!(CondensedMatrix::from_fn(1, |_, _| 1.0).values(), []);
+
+        let cancel = AtomicBool::new(false);
+        let result = CondensedMatrix::compute(
+            50,
+            |_, _| 1.0,
+            |progress| {
+                if progress.completed >= 10 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            },
+            &cancel,
+        );
+        assert_eq!(result, Err(Cancelled));
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let distance = |a: usize, b: usize| (a * 10 + b) as f64;
+        let mut matrix = CondensedMatrix::from_fn(3, distance);
+        let calls = Mutex::new(Vec::new());
+
+        let index = matrix.push(|other| {
+            calls.lock().unwrap().push(other);
+            distance(other, 3)
+        });
+        assert_eq!(index, 3);
+        assert_eq!(calls.into_inner().unwrap().len(), 3);
+        assert_eq!(matrix, CondensedMatrix::from_fn(4, distance));
+
+        matrix.remove(1);
+        assert_eq!(matrix.size(), 3);
+        assert_eq!(matrix.get(0, 1), 2.0);
+        assert_eq!(matrix.get(1, 2), 23.0);
+        assert_eq!(matrix.select(&[2, 0]).get(0, 1), 3.0);
+
+        let mut bytes = Vec::new();
+        matrix.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 4 + 8 + 3 * 8);
+        assert_eq!(CondensedMatrix::read(bytes.as_slice()).unwrap(), matrix);
+        assert!(CondensedMatrix::read(&bytes[..20]).is_err());
+        assert!(CondensedMatrix::read(&b"JSON"[..]).is_err());
+
+        let mut huge = MAGIC.to_vec();
+        huge.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(CondensedMatrix::read(huge.as_slice()).is_err());
+        huge[4..12].copy_from_slice(&(1u64 << 31).to_le_bytes());
+        assert_eq!(
+            CondensedMatrix::read(huge.as_slice()).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}